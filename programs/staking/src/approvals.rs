@@ -0,0 +1,104 @@
+//! m-of-n approval of the sensitive actions of a project.
+//! Once the owner sets approvers and a threshold, allowlist changes, sunsets, withdrawals past
+//! `approval_limit` a day and changes of the approvers themselves go through a `Proposal`: an
+//! approver proposes the action, the others approve it, and anyone executes it once the
+//! threshold is reached. Changing the approvers expires the proposals of the previous ones.
+
+use anchor_lang::prelude::*;
+
+use crate::{Proposal, Staking};
+use crate::errors::StakingError;
+
+/// Period `approval_limit` applies to (in seconds)
+pub const WITHDRAWAL_PERIOD: i64 = 24 * 60 * 60;
+
+impl Staking {
+    /// Returns true when sensitive actions need the approval of the approvers
+    pub fn uses_approvals(&self) -> bool {
+        self.approval_threshold != 0
+    }
+
+    /// Returns the index of `key` in the approvers, if it is one
+    pub fn approver_index(&self, key: &Pubkey) -> Option<usize> {
+        self.approvers.iter().position(|approver| approver == key)
+    }
+
+    /// Counts `amount` in the withdrawals of the current period, which need a proposal once they
+    /// add up past `approval_limit`
+    pub fn record_withdrawal(&mut self, amount: u64, now: i64) -> Result<()> {
+        if now >= self.withdrawal_period_start.saturating_add(WITHDRAWAL_PERIOD) {
+            self.withdrawal_period_start = now;
+            self.withdrawn_in_period = 0;
+        }
+
+        let withdrawn = self.withdrawn_in_period
+            .checked_add(amount)
+            .ok_or_else(|| error!(StakingError::MathOverflow))?;
+        if self.uses_approvals() && withdrawn > self.approval_limit {
+            return err!(StakingError::NeedsApproval);
+        }
+        self.withdrawn_in_period = withdrawn;
+
+        Ok(())
+    }
+
+    /// Replaces the approvers, expiring the pending proposals. A zero threshold disables approvals
+    pub fn set_approvers(
+        &mut self,
+        approvers: Vec<Pubkey>,
+        approval_threshold: u8,
+        approval_limit: u64,
+    ) -> Result<()> {
+        validate_approvers(&approvers, approval_threshold)?;
+
+        self.approvers = approvers;
+        self.approval_threshold = approval_threshold;
+        self.approval_limit = approval_limit;
+        self.approvers_nonce = self.approvers_nonce
+            .checked_add(1)
+            .ok_or_else(|| error!(StakingError::MathOverflow))?;
+
+        Ok(())
+    }
+}
+
+/// Checks the approvers are distinct, fit in the approval bits and can reach `approval_threshold`
+pub fn validate_approvers(approvers: &[Pubkey], approval_threshold: u8) -> Result<()> {
+    if approvers.len() > Staking::MAX_APPROVERS || approval_threshold as usize > approvers.len() {
+        return err!(StakingError::InvalidApprovers);
+    }
+    for (i, approver) in approvers.iter().enumerate() {
+        if *approver == Pubkey::default() || approvers[..i].contains(approver) {
+            return err!(StakingError::InvalidApprovers);
+        }
+    }
+
+    Ok(())
+}
+
+impl Proposal {
+    /// Records the approval of `approver`, who must be one of the current approvers
+    pub fn approve(&mut self, staking: &Staking, approver: &Pubkey) -> Result<()> {
+        if self.approvers_nonce != staking.approvers_nonce {
+            return err!(StakingError::ProposalExpired);
+        }
+        let index = staking
+            .approver_index(approver)
+            .ok_or_else(|| error!(StakingError::NotApprover))?;
+        self.approvals |= 1 << index;
+
+        Ok(())
+    }
+
+    /// Checks the proposal reached the threshold of the current approvers
+    pub fn check_approved(&self, staking: &Staking) -> Result<()> {
+        if self.approvers_nonce != staking.approvers_nonce {
+            return err!(StakingError::ProposalExpired);
+        }
+        if !staking.uses_approvals() || self.approvals.count_ones() < staking.approval_threshold as u32 {
+            return err!(StakingError::ProposalNotApproved);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+//! Minimal client for Metaplex Bubblegum, the compressed NFT program.
+//! Only builds the transfer used to custody staked cNFTs, the leaf proof is checked by the CPI
+
+use anchor_lang::prelude::*;
+use solana_program::instruction::{AccountMeta, Instruction};
+
+use crate::CompressedLeaf;
+
+solana_program::declare_id!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+
+/// SPL Noop program, used by Bubblegum to log leaf changes
+pub mod spl_noop {
+    solana_program::declare_id!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+}
+
+/// SPL Account Compression program, owning the concurrent merkle trees
+pub mod spl_account_compression {
+    solana_program::declare_id!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+}
+
+/// Anchor discriminator of Bubblegum's `transfer` instruction
+const TRANSFER_DISCRIMINATOR: [u8; 8] = [163, 52, 200, 231, 140, 3, 69, 186];
+
+/// Returns the asset id of the compressed NFT minted in `merkle_tree` with `nonce`
+pub fn asset_id(merkle_tree: &Pubkey, nonce: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"asset", merkle_tree.as_ref(), &nonce.to_le_bytes()],
+        &ID,
+    ).0
+}
+
+/// Builds the instruction transferring the compressed NFT `leaf` from `leaf_owner`, which signs,
+/// to `new_leaf_owner`. `proof` holds the tree nodes not covered by the canopy
+pub fn transfer(
+    tree_authority: Pubkey,
+    leaf_owner: Pubkey,
+    leaf_delegate: Pubkey,
+    new_leaf_owner: Pubkey,
+    merkle_tree: Pubkey,
+    proof: &[AccountInfo],
+    leaf: &CompressedLeaf,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(tree_authority, false),
+        AccountMeta::new_readonly(leaf_owner, true),
+        AccountMeta::new_readonly(leaf_delegate, false),
+        AccountMeta::new_readonly(new_leaf_owner, false),
+        AccountMeta::new(merkle_tree, false),
+        AccountMeta::new_readonly(spl_noop::ID, false),
+        AccountMeta::new_readonly(spl_account_compression::ID, false),
+        AccountMeta::new_readonly(solana_program::system_program::ID, false),
+    ];
+    accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(node.key(), false)));
+
+    let mut data = TRANSFER_DISCRIMINATOR.to_vec();
+    data.extend(leaf.try_to_vec().unwrap());
+
+    Instruction {
+        program_id: ID,
+        accounts,
+        data,
+    }
+}
@@ -0,0 +1,435 @@
+//! Checks an NFT can be staked in a project, and with which rarity multiplier.
+//!
+//! An attestation is the Ed25519 signature, by the rarity authority, of the message
+//! `staking key (32) || mint (32) || rarity multiplier (u64 LE) || expiry (i64 LE)`,
+//! with the signature, public key and message inside the Ed25519 instruction.
+//!
+//! The accounts needed are passed first in the remaining accounts of the stake instructions,
+//! in this order and only when used:
+//! - the NFT metadata, when the project checks the collection or the creator and the
+//!   instruction has no `metadata` account
+//! - the `Rarity` account of the mint
+//! - the instructions sysvar, for an attestation when the instruction has no `instructions` account
+//! - the `RootRegistry`, when the proof is for a `root_index` of the registry
+
+use std::slice::Iter;
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_lang::solana_program::{ed25519_program, sysvar};
+use mpl_token_metadata::state::{Metadata, TokenMetadataAccount};
+
+use crate::{Staking, StakedNft, RootRegistry, Rarity};
+use crate::merkle_proof;
+use crate::mpl_core;
+use crate::errors::StakingError;
+
+/// How an NFT was accepted in a project
+#[derive(Clone, Copy, Default)]
+pub struct Eligible {
+    /// The rarity multiplier the NFT stakes with
+    pub rarity_multiplier: u64,
+
+    /// The verified collection the NFT was accepted through, if any
+    pub collection: Option<Pubkey>,
+
+    /// The registry root the NFT was proven against, if any
+    pub root_index: Option<u8>,
+}
+
+impl Eligible {
+    fn with_rarity(rarity_multiplier: u64) -> Self {
+        Eligible { rarity_multiplier, ..Default::default() }
+    }
+}
+
+impl Staking {
+    /// Returns true when NFTs are checked against a verified collection instead of the allowlist
+    pub fn uses_collection(&self) -> bool {
+        self.collection != Pubkey::default()
+            || self.reward_groups.iter().any(|group| group.collection != Pubkey::default())
+    }
+
+    /// Returns true if NFTs of the verified `collection` can be staked
+    pub fn accepts_collection(&self, collection: &Pubkey) -> bool {
+        *collection != Pubkey::default()
+            && (*collection == self.collection
+                || self.reward_groups.iter().any(|group| group.collection == *collection))
+    }
+
+    /// Returns the index of the reward group an NFT accepted as `eligible` earns in, if any
+    pub fn group_of(&self, eligible: &Eligible) -> Option<u8> {
+        self.reward_groups
+            .iter()
+            .position(|group| match (eligible.collection, eligible.root_index) {
+                (Some(collection), _) => group.collection == collection,
+                (None, Some(root_index)) => group.root_index == Some(root_index),
+                (None, None) => false,
+            })
+            .map(|index| index as u8)
+    }
+
+    /// Returns true when NFTs are checked against a verified first creator
+    pub fn uses_creator(&self) -> bool {
+        self.creator != Pubkey::default()
+    }
+
+    /// Returns true when NFTs are checked against their metadata instead of the allowlist
+    pub fn uses_metadata_rules(&self) -> bool {
+        self.uses_collection() || self.uses_creator()
+    }
+
+    /// Returns true when rarity attestations are accepted
+    pub fn uses_attestations(&self) -> bool {
+        self.rarity_authority != Pubkey::default()
+    }
+
+    /// Returns the time staked NFTs have to revalidate after the last root change
+    pub fn revalidation_deadline(&self) -> i64 {
+        self.root_updated_at.saturating_add(self.revalidation_grace)
+    }
+
+    /// Replaces the allowlist root. Staked NFTs have to prove themselves against a new root
+    pub fn set_root(&mut self, root: [u8; 32], now: i64) {
+        if root != self.root {
+            self.root_updated_at = now;
+        }
+        self.root = root;
+    }
+}
+
+impl StakedNft {
+    /// Returns true if the NFT was staked or proved its rarity since the root last changed
+    pub fn is_validated(&self, staking: &Staking) -> bool {
+        self.staked_at.max(self.validated_at) >= staking.root_updated_at
+    }
+}
+
+/// Length of an attestation message: staking key, mint, rarity multiplier and expiry
+const ATTESTATION_LEN: usize = 32 + 32 + 8 + 8;
+
+/// Returns the allowlist root a proof is for: `Staking.root`, or the active root at
+/// `root_index` in the project `registry`
+pub fn allowlist_root(
+    staking: &Staking,
+    root_index: Option<u8>,
+    registry: Option<&AccountInfo>,
+) -> Result<[u8; 32]> {
+    let index = match root_index {
+        Some(index) => index as usize,
+        None => return Ok(staking.root),
+    };
+
+    let registry = registry.ok_or_else(|| error!(StakingError::InvalidAccount))?;
+    let registry = Account::<RootRegistry>::try_from(registry)?;
+    if registry.key != staking.key {
+        return err!(StakingError::InvalidAccount);
+    }
+
+    match registry.roots.get(index) {
+        Some(root) if root.active => Ok(root.root),
+        _ => err!(StakingError::InvalidRootIndex),
+    }
+}
+
+/// Checks `mint`, or the asset id standing for it, is in the allowlist of `root` with
+/// `rarity_multiplier`
+pub fn verify_allowlist(
+    root: [u8; 32],
+    mint: &Pubkey,
+    proof: Vec<[u8; 32]>,
+    rarity_multiplier: u64,
+) -> Result<u64> {
+    let node = solana_program::keccak::hashv(&[
+        &[0x00],
+        &mint.to_bytes(),
+        &rarity_multiplier.to_le_bytes(),
+    ]);
+    if !merkle_proof::verify(proof, root, node.0) {
+        return err!(StakingError::InvalidProof);
+    }
+
+    Ok(rarity_multiplier)
+}
+
+/// Returns the rarity `account` registers for `mint` in the project
+pub fn registered_rarity(staking: &Staking, mint: &Pubkey, account: &AccountInfo) -> Result<u64> {
+    let rarity = Account::<Rarity>::try_from(account)?;
+    if rarity.key != staking.key || rarity.mint != *mint {
+        return err!(StakingError::InvalidAccount);
+    }
+    if rarity.rarity_multiplier == 0 {
+        return err!(StakingError::NotInRarityRegistry);
+    }
+
+    Ok(rarity.rarity_multiplier)
+}
+
+/// Returns true if `account` is a `Rarity` account of this program
+fn is_rarity_account(account: &AccountInfo) -> bool {
+    if *account.owner != crate::ID {
+        return false;
+    }
+    match account.try_borrow_data() {
+        Ok(data) => data.len() >= 8 && data[..8] == Rarity::discriminator(),
+        Err(_) => false,
+    }
+}
+
+/// Takes the rarity registered for `mint` if the next account is a `Rarity` account
+fn take_registered_rarity(
+    staking: &Staking,
+    mint: &Pubkey,
+    accounts: &mut Iter<AccountInfo>,
+) -> Result<Option<u64>> {
+    match accounts.as_slice().first() {
+        Some(account) if is_rarity_account(account) => {
+            accounts.next();
+            registered_rarity(staking, mint, account).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Returns the public key and the message signed by a single signature Ed25519 instruction,
+/// or None if the instruction reads them from other instructions
+fn ed25519_signed(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    // Signature count, padding, then the offsets of the signature
+    if data.len() < 2 + 14 || data[0] != 1 {
+        return None;
+    }
+    let offset = |i: usize| u16::from_le_bytes([data[2 + i], data[3 + i]]);
+    let signature_instruction = offset(2);
+    let public_key = offset(4) as usize;
+    let public_key_instruction = offset(6);
+    let message = offset(8) as usize;
+    let message_size = offset(10) as usize;
+    let message_instruction = offset(12);
+
+    // u16::MAX stands for the Ed25519 instruction itself
+    if signature_instruction != u16::MAX
+        || public_key_instruction != u16::MAX
+        || message_instruction != u16::MAX
+    {
+        return None;
+    }
+
+    Some((
+        data.get(public_key..public_key + 32)?,
+        data.get(message..message + message_size)?,
+    ))
+}
+
+/// Returns the rarity attested for `mint` when the instruction before the current one is an
+/// Ed25519 signature by the project rarity authority, None if there is no such instruction
+pub fn attested_rarity(staking: &Staking, mint: &Pubkey, instructions: &AccountInfo) -> Result<Option<u64>> {
+    let current = sysvar::instructions::load_current_index_checked(instructions)?;
+    if current == 0 {
+        return Ok(None);
+    }
+    let instruction = sysvar::instructions::load_instruction_at_checked(
+        current as usize - 1,
+        instructions,
+    )?;
+    if instruction.program_id != ed25519_program::ID {
+        return Ok(None);
+    }
+
+    let (signer, message) = ed25519_signed(&instruction.data)
+        .ok_or_else(|| error!(StakingError::InvalidAttestation))?;
+    if signer != staking.rarity_authority.as_ref() {
+        return Ok(None);
+    }
+
+    if message.len() != ATTESTATION_LEN
+        || message[..32] != staking.key.to_bytes()
+        || message[32..64] != mint.to_bytes()
+    {
+        return err!(StakingError::InvalidAttestation);
+    }
+    let rarity_multiplier = u64::from_le_bytes(message[64..72].try_into().unwrap());
+    let expiry = i64::from_le_bytes(message[72..80].try_into().unwrap());
+
+    if expiry < Clock::get()?.unix_timestamp {
+        return err!(StakingError::AttestationExpired);
+    }
+    if rarity_multiplier == 0 {
+        return err!(StakingError::InvalidAttestation);
+    }
+
+    Ok(Some(rarity_multiplier))
+}
+
+/// Takes the rarity of `mint` from the rarity registry or from an attestation, when the
+/// staker brings one. `instructions` is taken from `accounts` if the instruction has none
+fn take_rarity(
+    staking: &Staking,
+    mint: &Pubkey,
+    instructions: Option<&AccountInfo>,
+    accounts: &mut Iter<AccountInfo>,
+) -> Result<Option<u64>> {
+    if let Some(rarity) = take_registered_rarity(staking, mint, accounts)? {
+        return Ok(Some(rarity));
+    }
+
+    let passed = match accounts.as_slice().first() {
+        Some(account) if sysvar::instructions::check_id(account.key) => accounts.next(),
+        _ => None,
+    };
+    if !staking.uses_attestations() {
+        return Ok(None);
+    }
+
+    match (instructions, passed) {
+        (Some(instructions), _) => attested_rarity(staking, mint, instructions),
+        (None, Some(instructions)) => attested_rarity(staking, mint, instructions),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Checks `mint` is listed by the project, in the rarity registry, an attestation or an
+/// allowlist, and returns how it was accepted
+#[allow(clippy::too_many_arguments)]
+pub fn verify_listed(
+    staking: &Staking,
+    mint: &Pubkey,
+    instructions: Option<&AccountInfo>,
+    accounts: &mut Iter<AccountInfo>,
+    proof: Vec<[u8; 32]>,
+    root_index: Option<u8>,
+    rarity_multiplier: u64,
+) -> Result<Eligible> {
+    if let Some(rarity) = take_rarity(staking, mint, instructions, accounts)? {
+        return Ok(Eligible::with_rarity(rarity));
+    }
+
+    let registry = match root_index {
+        Some(_) => accounts.next(),
+        None => None,
+    };
+    let root = allowlist_root(staking, root_index, registry)?;
+    Ok(Eligible {
+        rarity_multiplier: verify_allowlist(root, mint, proof, rarity_multiplier)?,
+        collection: None,
+        root_index,
+    })
+}
+
+/// Returns the Token Metadata account of `mint`
+fn load_metadata(mint: &Pubkey, metadata: Option<&AccountInfo>) -> Result<Metadata> {
+    let metadata = metadata.ok_or_else(|| error!(StakingError::InvalidAccount))?;
+    if *metadata.owner != mpl_token_metadata::ID {
+        return err!(StakingError::InvalidAccount);
+    }
+
+    let metadata = Metadata::from_account_info(metadata)?;
+    if metadata.mint != *mint {
+        return err!(StakingError::InvalidAccount);
+    }
+
+    Ok(metadata)
+}
+
+/// Returns the verified collection of `metadata` if the project accepts it
+fn accepted_collection(staking: &Staking, metadata: &Metadata) -> Option<Pubkey> {
+    match &metadata.collection {
+        Some(collection) if collection.verified && staking.accepts_collection(&collection.key) => {
+            Some(collection.key)
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if the first creator of `metadata` is the project creator, verified
+fn from_creator(staking: &Staking, metadata: &Metadata) -> bool {
+    match metadata.data.creators.as_ref().and_then(|creators| creators.first()) {
+        Some(creator) => creator.verified && creator.address == staking.creator,
+        None => false,
+    }
+}
+
+/// Checks a Token Metadata NFT can be staked and returns how it was accepted.
+/// `metadata` and `instructions` are taken from `accounts` if the instruction has none.
+/// An NFT matching both rules gets the collection rarity, unless the rarity registry or an
+/// attestation has one for it
+#[allow(clippy::too_many_arguments)]
+pub fn verify_nft(
+    staking: &Staking,
+    mint: &Pubkey,
+    metadata: Option<&AccountInfo>,
+    instructions: Option<&AccountInfo>,
+    accounts: &mut Iter<AccountInfo>,
+    proof: Vec<[u8; 32]>,
+    root_index: Option<u8>,
+    rarity_multiplier: u64,
+) -> Result<Eligible> {
+    if !staking.uses_metadata_rules() {
+        return verify_listed(staking, mint, instructions, accounts, proof, root_index, rarity_multiplier);
+    }
+
+    let metadata = match metadata {
+        Some(metadata) => load_metadata(mint, Some(metadata))?,
+        None => load_metadata(mint, accounts.next())?,
+    };
+    let collection = accepted_collection(staking, &metadata);
+    let rule_rarity = if collection.is_some() {
+        staking.collection_rarity
+    } else if staking.uses_creator() && from_creator(staking, &metadata) {
+        staking.creator_rarity
+    } else if staking.uses_collection() {
+        return err!(StakingError::NotInCollection);
+    } else {
+        return err!(StakingError::NotFromCreator);
+    };
+
+    Ok(Eligible {
+        rarity_multiplier: take_rarity(staking, mint, instructions, accounts)?.unwrap_or(rule_rarity),
+        collection,
+        root_index: None,
+    })
+}
+
+/// Checks a Metaplex Core asset can be staked and returns how it was accepted.
+/// Core assets are part of a collection when the collection is their update authority.
+/// They have no verified creators, so the creator rule never accepts them
+pub fn verify_core_asset(
+    staking: &Staking,
+    asset: &AccountInfo,
+    accounts: &mut Iter<AccountInfo>,
+    proof: Vec<[u8; 32]>,
+    root_index: Option<u8>,
+    rarity_multiplier: u64,
+) -> Result<Eligible> {
+    if !staking.uses_metadata_rules() {
+        return verify_listed(staking, asset.key, None, accounts, proof, root_index, rarity_multiplier);
+    }
+
+    let collection = match mpl_core::collection_of(asset) {
+        Some(collection) if staking.accepts_collection(&collection) => collection,
+        _ => return err!(StakingError::NotInCollection),
+    };
+
+    Ok(Eligible {
+        rarity_multiplier: take_rarity(staking, asset.key, None, accounts)?.unwrap_or(staking.collection_rarity),
+        collection: Some(collection),
+        root_index: None,
+    })
+}
+
+/// Checks a compressed NFT can be staked and returns how it was accepted.
+/// Its metadata is hashed in the leaf, so compressed NFTs can only be staked from the allowlist,
+/// the rarity registry or an attestation
+pub fn verify_compressed(
+    staking: &Staking,
+    asset_id: &Pubkey,
+    accounts: &mut Iter<AccountInfo>,
+    proof: Vec<[u8; 32]>,
+    root_index: Option<u8>,
+    rarity_multiplier: u64,
+) -> Result<Eligible> {
+    if staking.uses_metadata_rules() {
+        return err!(StakingError::NotInCollection);
+    }
+
+    verify_listed(staking, asset_id, None, accounts, proof, root_index, rarity_multiplier)
+}
@@ -9,5 +9,107 @@ pub enum StakingError {
     InvalidProof,
 
     #[msg("Transaction fee payment failed")]
-    InvalidFee
+    InvalidFee,
+
+    #[msg("Reward calculation overflowed")]
+    MathOverflow,
+
+    #[msg("Account does not belong to this program")]
+    InvalidAccount,
+
+    #[msg("Staking has ended")]
+    StakingEnded,
+
+    #[msg("Staking schedule is invalid or changes the past")]
+    InvalidSchedule,
+
+    #[msg("Withdrawal would leave less than the rewards owed to stakers")]
+    WithdrawBelowLiability,
+
+    #[msg("Lock tier is invalid")]
+    InvalidLockTier,
+
+    #[msg("Staked NFT is still locked")]
+    StakeLocked,
+
+    #[msg("Loyalty curve is invalid")]
+    InvalidLoyaltyCurve,
+
+    #[msg("Early unstake penalty is invalid")]
+    InvalidPenalty,
+
+    #[msg("Token weights are invalid")]
+    InvalidTokenWeights,
+
+    #[msg("Token can't be staked in this project")]
+    TokenNotStakeable,
+
+    #[msg("Amount is zero or exceeds the staked amount")]
+    InvalidAmount,
+
+    #[msg("NFT is not verified in the project collection")]
+    NotInCollection,
+
+    #[msg("Rarity multiplier of the eligibility rule is invalid")]
+    InvalidRuleRarity,
+
+    #[msg("NFT first creator is not the verified project creator")]
+    NotFromCreator,
+
+    #[msg("Root registry is full")]
+    RootRegistryFull,
+
+    #[msg("Root index is invalid or the root is retired")]
+    InvalidRootIndex,
+
+    #[msg("NFT has no rarity in the project rarity registry")]
+    NotInRarityRegistry,
+
+    #[msg("Rarity attestation is invalid")]
+    InvalidAttestation,
+
+    #[msg("Rarity attestation has expired")]
+    AttestationExpired,
+
+    #[msg("Revalidation grace period is invalid")]
+    InvalidRevalidationGrace,
+
+    #[msg("Stake can't be revoked during the revalidation grace period")]
+    RevalidationGracePeriod,
+
+    #[msg("Reward groups are invalid")]
+    InvalidRewardGroups,
+
+    #[msg("Reward group is full")]
+    RewardGroupFull,
+
+    #[msg("Signer is not the proposed owner of the staking")]
+    NotPendingOwner,
+
+    #[msg("Signer lacks the permission for this instruction")]
+    Unauthorized,
+
+    #[msg("Role is invalid")]
+    InvalidRole,
+
+    #[msg("Role table is full")]
+    RoleTableFull,
+
+    #[msg("Action needs the approval of the project approvers")]
+    NeedsApproval,
+
+    #[msg("Approvers or threshold are invalid")]
+    InvalidApprovers,
+
+    #[msg("Signer is not an approver of the project")]
+    NotApprover,
+
+    #[msg("Proposal was made for previous approvers")]
+    ProposalExpired,
+
+    #[msg("Proposal has not reached the approval threshold")]
+    ProposalNotApproved,
+
+    #[msg("Proposal action doesn't match the instruction")]
+    InvalidProposal,
 }
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::Staking;
+use crate::errors::StakingError;
+
+#[derive(Accounts)]
+pub struct AcceptOwner<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        constraint = staking.pending_owner == pending_owner.key() @ StakingError::NotPendingOwner,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The wallet proposed to own the staking
+    pub pending_owner: Signer<'info>,
+}
+
+/// Makes the proposed owner the owner of the staking
+pub fn handler(ctx: Context<AcceptOwner>) -> Result<()> {
+
+    let staking = &mut ctx.accounts.staking;
+    staking.owner = staking.pending_owner;
+    staking.pending_owner = Pubkey::default();
+
+    msg!("Owner set: {}", staking.owner);
+
+    Ok(())
+}
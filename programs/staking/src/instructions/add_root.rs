@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, RootRegistry, NamedRoot};
+use crate::errors::StakingError;
+use crate::roles;
+
+#[derive(Accounts)]
+pub struct AddRoot<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        constraint = staking.can(owner.key, roles::ALLOWLIST) @ StakingError::Unauthorized,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The merkle roots of the project, created on first use
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = RootRegistry::LEN,
+        seeds = [
+            b"roots",
+            staking.key.as_ref()
+        ],
+        bump
+    )]
+    pub root_registry: Box<Account<'info, RootRegistry>>,
+
+    /// The owner of the staking, or a wallet with the allowlist permission
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Adds a merkle root to the registry, in the first retired slot or a new one.
+/// Proofs of the other roots stay valid. Once approvals are set, it goes through a proposal
+pub fn handler(
+    ctx: Context<AddRoot>,
+    name: [u8; 32],
+    root: [u8; 32],
+) -> Result<()> {
+
+    if ctx.accounts.staking.uses_approvals() {
+        return err!(StakingError::NeedsApproval);
+    }
+
+    let registry = &mut ctx.accounts.root_registry;
+    registry.bump = *ctx.bumps.get("root_registry").unwrap();
+    registry.key = ctx.accounts.staking.key;
+
+    add_root(&mut ctx.accounts.staking, registry, name, root)
+}
+
+/// Adds a merkle root to the registry. A reused slot starts out of any reward group
+pub fn add_root(
+    staking: &mut Staking,
+    registry: &mut RootRegistry,
+    name: [u8; 32],
+    root: [u8; 32],
+) -> Result<()> {
+    let named_root = NamedRoot {
+        name,
+        root,
+        active: true,
+    };
+
+    let index = match registry.roots.iter().position(|root| !root.active) {
+        Some(index) => {
+            registry.roots[index] = named_root;
+            for group in staking.reward_groups
+                .iter_mut()
+                .filter(|group| group.root_index == Some(index as u8))
+            {
+                group.root_index = None;
+            }
+            index
+        }
+        None => {
+            if registry.roots.len() >= RootRegistry::MAX_ROOTS {
+                return err!(StakingError::RootRegistryFull);
+            }
+            registry.roots.push(named_root);
+            registry.roots.len() - 1
+        }
+    };
+
+    msg!("Root added: {}", index);
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, Proposal};
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    /// The Staking state account
+    pub staking: Account<'info, Staking>,
+
+    /// The proposal to approve
+    #[account(
+        mut,
+        constraint = proposal.key == staking.key,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    /// The approver
+    pub approver: Signer<'info>,
+}
+
+/// Approves a proposal of the project
+pub fn handler(ctx: Context<ApproveProposal>) -> Result<()> {
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.approve(&ctx.accounts.staking, ctx.accounts.approver.key)?;
+
+    msg!("Proposal approved: {} of {}", proposal.approvals.count_ones(), ctx.accounts.staking.approval_threshold);
+
+    Ok(())
+}
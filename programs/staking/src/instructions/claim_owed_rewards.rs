@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, OwedRewards};
+use crate::rewards;
+use crate::errors::StakingError;
+use crate::token_interface;
+
+#[derive(Accounts)]
+pub struct ClaimOwedRewards<'info> {
+    /// The staking state
+    #[account(
+        mut,
+        seeds = [
+            b"staking",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.staking,
+        has_one = mint,
+        has_one = rewards_account
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+
+    /// The account holding staking tokens, staking rewards and community funds
+    #[account(
+        mut,
+        seeds = [
+            b"escrow",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.escrow
+    )]
+    /// CHECK: TBD
+    pub escrow: AccountInfo<'info>,
+
+    /// The rewards owed to the staker
+    #[account(
+        mut,
+        seeds = [
+            b"owed",
+            staking.key.as_ref(),
+            staker.key().as_ref()
+        ],
+        bump = owed_rewards.bump,
+        has_one = staker
+    )]
+    pub owed_rewards: Box<Account<'info, OwedRewards>>,
+
+    /// The staker the rewards are owed to
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    /// The mint of the reward token
+    #[account(mut)]
+    /// CHECK: TBD
+    pub mint: AccountInfo<'info>,
+
+    /// The user account receiving rewards
+    #[account(
+        mut,
+        constraint = token_interface::is_account_of(&staker_account, &mint.key(), &staker.key())
+    )]
+    /// CHECK: checked by constraint
+    pub staker_account: UncheckedAccount<'info>,
+
+    /// The account that holds the rewards token
+    #[account(
+        mut,
+        seeds = [
+            b"rewards",
+            staking.key.as_ref(),
+            staking.mint.as_ref()
+        ],
+        bump = staking.bumps.rewards,
+    )]
+    /// CHECK: token account of the reward mint, read through the token interface
+    pub rewards_account: UncheckedAccount<'info>,
+
+    /// The program for interacting with the reward token, legacy or Token-2022
+    #[account(constraint = token_interface::is_token_program(token_program.key) @ StakingError::InvalidAccount)]
+    /// CHECK: checked by constraint
+    pub token_program: UncheckedAccount<'info>,
+}
+
+/// Pays the rewards owed to a staker, as far as the vault allows
+pub fn handler(ctx: Context<ClaimOwedRewards>) -> Result<()> {
+    let staking = &mut ctx.accounts.staking;
+    let owed_rewards = &mut ctx.accounts.owed_rewards;
+
+    let amount_paid = rewards::pay_or_owe(
+        staking,
+        owed_rewards,
+        0,
+        token_interface::amount(&ctx.accounts.rewards_account)?,
+    )?;
+
+    let seeds = &[
+        b"escrow".as_ref(),
+        staking.key.as_ref(),
+        &[staking.bumps.escrow],
+    ];
+    let signer = &[&seeds[..]];
+
+    if amount_paid > 0 {
+        token_interface::transfer_checked(
+            &ctx.accounts.token_program,
+            &ctx.accounts.rewards_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.staker_account,
+            &ctx.accounts.escrow,
+            amount_paid,
+            signer,
+        )?;
+    }
+
+    msg!("Owed rewards claimed: {}", amount_paid);
+
+    Ok(())
+}
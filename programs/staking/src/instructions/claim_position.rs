@@ -0,0 +1,143 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, StakedPosition, OwedRewards};
+use crate::rewards;
+use crate::errors::StakingError;
+use crate::token_interface;
+
+#[derive(Accounts)]
+pub struct ClaimPosition<'info> {
+    /// The staking state
+    #[account(
+        mut,
+        seeds = [
+            b"staking",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.staking,
+        has_one = mint,
+        has_one = rewards_account
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+
+    /// The account holding staking tokens, staking rewards and community funds
+    #[account(
+        seeds = [
+            b"escrow",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.escrow
+    )]
+    /// CHECK: TBD
+    pub escrow: AccountInfo<'info>,
+
+    /// The position rewards are claimed for
+    #[account(
+        mut,
+        seeds = [
+            b"position",
+            staking.key.as_ref(),
+            staker.key().as_ref(),
+            staked_position.mint.as_ref()
+        ],
+        bump = staked_position.bump,
+        has_one = staker
+    )]
+    pub staked_position: Box<Account<'info, StakedPosition>>,
+
+    /// The owner of the staked tokens
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    /// The mint of the reward token
+    /// CHECK: TBD
+    pub mint: AccountInfo<'info>,
+
+    /// The user account receiving rewards
+    #[account(
+        mut,
+        constraint = token_interface::is_account_of(&staker_account, &mint.key(), &staker.key())
+    )]
+    /// CHECK: checked by constraint
+    pub staker_account: UncheckedAccount<'info>,
+
+    /// The rewards owed to the staker when the vault could not pay in full
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = OwedRewards::LEN,
+        seeds = [
+            b"owed",
+            staking.key.as_ref(),
+            staker.key().as_ref()
+        ],
+        bump
+    )]
+    pub owed_rewards: Box<Account<'info, OwedRewards>>,
+
+    /// The account that will hold the rewards token
+    #[account(
+        mut,
+        seeds = [
+            b"rewards",
+            staking.key.as_ref(),
+            staking.mint.as_ref()
+        ],
+        bump = staking.bumps.rewards,
+    )]
+    /// CHECK: token account of the reward mint, read through the token interface
+    pub rewards_account: UncheckedAccount<'info>,
+
+    /// The program for interacting with the reward token, legacy or Token-2022
+    #[account(constraint = token_interface::is_token_program(token_program.key) @ StakingError::InvalidAccount)]
+    /// CHECK: checked by constraint
+    pub token_program: UncheckedAccount<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Claims the rewards of a fungible token position
+pub fn handler(ctx: Context<ClaimPosition>) -> Result<()> {
+    let staking = &mut ctx.accounts.staking;
+    let staked_position = &mut ctx.accounts.staked_position;
+
+    rewards::settle_position(staking, staked_position, ctx.accounts.clock.unix_timestamp)?;
+    let rewards_amount = rewards::take_position_rewards(staking, staked_position);
+
+    // Rewards owed to the staker, created on first use
+    let amount_paid = rewards::pay_or_owe_staker(
+        staking,
+        &mut ctx.accounts.owed_rewards,
+        *ctx.bumps.get("owed_rewards").unwrap(),
+        ctx.accounts.staker.key(),
+        rewards_amount,
+        token_interface::amount(&ctx.accounts.rewards_account)?,
+    )?;
+
+    let seeds = &[
+        b"escrow".as_ref(),
+        staking.key.as_ref(),
+        &[staking.bumps.escrow],
+    ];
+    let signer = &[&seeds[..]];
+
+    if amount_paid > 0 {
+        let received = token_interface::transfer_checked(
+            &ctx.accounts.token_program,
+            &ctx.accounts.rewards_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.staker_account,
+            &ctx.accounts.escrow,
+            amount_paid,
+            signer,
+        )?;
+        msg!("Rewards paid: {}", received);
+    }
+
+    msg!("Rewards claimed");
+
+    Ok(())
+}
@@ -1,12 +1,15 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
-use crate::{Staking, StakedNft};
+use crate::{Staking, StakedNft, OwedRewards};
+use crate::rewards;
+use crate::errors::StakingError;
+use crate::token_interface;
 
 #[derive(Accounts)]
 pub struct ClaimStaking<'info> {
     /// The staking state
     #[account(
+        mut,
         seeds = [
             b"staking",
             staking.key.as_ref()
@@ -37,7 +40,8 @@ pub struct ClaimStaking<'info> {
             staked_nft.mint.as_ref()
         ],
         bump = staked_nft.bumps.staked_nft,
-        has_one = staker
+        has_one = staker,
+        constraint = staked_nft.key == staking.key @ StakingError::InvalidAccount
     )]
     pub staked_nft: Box<Account<'info, StakedNft>>,
 
@@ -53,11 +57,24 @@ pub struct ClaimStaking<'info> {
     /// The user account receiving rewards
     #[account(
         mut, 
-        constraint = 
-            staker_account.owner == staker.key() &&
-            staker_account.mint == mint.key()
+        constraint = token_interface::is_account_of(&staker_account, &mint.key(), &staker.key())
+    )]
+    /// CHECK: checked by constraint
+    pub staker_account: UncheckedAccount<'info>,
+
+    /// The rewards owed to the staker when the vault could not pay in full
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = OwedRewards::LEN,
+        seeds = [
+            b"owed",
+            staking.key.as_ref(),
+            staker.key().as_ref()
+        ],
+        bump
     )]
-    pub staker_account: Box<Account<'info, TokenAccount>>,
+    pub owed_rewards: Box<Account<'info, OwedRewards>>,
 
     /// The account that will hold the rewards token
     #[account(
@@ -69,11 +86,13 @@ pub struct ClaimStaking<'info> {
         ],
         bump = staking.bumps.rewards,
     )]
-    pub rewards_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: token account of the reward mint, read through the token interface
+    pub rewards_account: UncheckedAccount<'info>,
 
-    /// The program for interacting with the token
-    #[account(address = token::ID)]
-    pub token_program: Program<'info, Token>,
+    /// The program for interacting with the reward token, legacy or Token-2022
+    #[account(constraint = token_interface::is_token_program(token_program.key) @ StakingError::InvalidAccount)]
+    /// CHECK: checked by constraint
+    pub token_program: UncheckedAccount<'info>,
 
     /// Clock account used to know the time
     pub clock: Sysvar<'info, Clock>,
@@ -85,15 +104,24 @@ pub struct ClaimStaking<'info> {
 /// Claims rewards for a staked token
 pub fn handler(ctx: Context<ClaimStaking>) -> Result<()> {
     // Update staking data
-    let staking = &ctx.accounts.staking;
+    let staking = &mut ctx.accounts.staking;
     let staked_nft = &mut ctx.accounts.staked_nft;
 
-    let rarity_multiplier = staked_nft.rarity_multiplier;
-    let seconds_elapsed = ctx.accounts.clock.unix_timestamp - staked_nft.last_claim;
-    let daily_rewards_adjusted = staking.daily_rewards * rarity_multiplier / 100;
-    let rewards_amount = daily_rewards_adjusted * (seconds_elapsed as u64) / 86400;
-    
-    staked_nft.last_claim = ctx.accounts.clock.unix_timestamp;
+    let rewards_amount = rewards::settle_rewards(
+        staking,
+        staked_nft,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+
+    // Rewards owed to the staker, created on first use
+    let amount_paid = rewards::pay_or_owe_staker(
+        staking,
+        &mut ctx.accounts.owed_rewards,
+        *ctx.bumps.get("owed_rewards").unwrap(),
+        ctx.accounts.staker.key(),
+        rewards_amount,
+        token_interface::amount(&ctx.accounts.rewards_account)?,
+    )?;
 
     let seeds = &[
         b"escrow".as_ref(),
@@ -102,16 +130,18 @@ pub fn handler(ctx: Context<ClaimStaking>) -> Result<()> {
     ];
     let signer = &[&seeds[..]];
 
-    let context = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.rewards_account.to_account_info(),
-            to: ctx.accounts.staker_account.to_account_info(),
-            authority: ctx.accounts.escrow.to_account_info(),
-        },
-        signer,
-    );
-    token::transfer(context, rewards_amount)?;
+    if amount_paid > 0 {
+        let received = token_interface::transfer_checked(
+            &ctx.accounts.token_program,
+            &ctx.accounts.rewards_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.staker_account,
+            &ctx.accounts.escrow,
+            amount_paid,
+            signer,
+        )?;
+        msg!("Rewards paid: {}", received);
+    }
 
     msg!("Rewards claimed");
 
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, Proposal, ProposedAction};
+use crate::approvals;
+use crate::errors::StakingError;
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    /// The Staking state account
+    #[account(mut)]
+    pub staking: Account<'info, Staking>,
+
+    /// The proposal, derived from the number of proposals of the project
+    #[account(
+        init,
+        payer = proposer,
+        space = Proposal::LEN,
+        seeds = [
+            b"proposal",
+            staking.key.as_ref(),
+            staking.proposal_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    /// The approver proposing the action, paying for the proposal
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    /// The System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Proposes `action` to the approvers, approved by the proposer
+pub fn handler(
+    ctx: Context<CreateProposal>,
+    action: ProposedAction,
+) -> Result<()> {
+
+    let staking = &mut ctx.accounts.staking;
+    if !staking.uses_approvals() {
+        return err!(StakingError::InvalidProposal);
+    }
+
+    match &action {
+        ProposedAction::SetApprovers { approvers, approval_threshold, .. } => {
+            approvals::validate_approvers(approvers, *approval_threshold)?;
+        }
+        ProposedAction::SetRarities { rarities } if rarities.len() > ProposedAction::MAX_RARITIES => {
+            return err!(StakingError::InvalidProposal);
+        }
+        ProposedAction::SetRewardGroups { reward_groups } if reward_groups.len() > Staking::MAX_REWARD_GROUPS => {
+            return err!(StakingError::InvalidRewardGroups);
+        }
+        _ => {}
+    }
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.bump = *ctx.bumps.get("proposal").unwrap();
+    proposal.key = staking.key;
+    proposal.proposer = ctx.accounts.proposer.key();
+    proposal.action = action;
+    proposal.approvers_nonce = staking.approvers_nonce;
+    proposal.approve(staking, &ctx.accounts.proposer.key())?;
+
+    staking.proposal_count = staking.proposal_count
+        .checked_add(1)
+        .ok_or_else(|| error!(StakingError::MathOverflow))?;
+
+    msg!("Proposal created: {}", proposal.key());
+
+    Ok(())
+}
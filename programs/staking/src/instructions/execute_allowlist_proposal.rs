@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, Proposal, ProposedAction, RootRegistry};
+use crate::errors::StakingError;
+use super::{add_root, retire_root, set_rarities};
+
+#[derive(Accounts)]
+pub struct ExecuteAllowlistProposal<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        seeds = [
+            b"staking",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.staking,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The approved proposal, closed once executed
+    #[account(
+        mut,
+        constraint = proposal.key == staking.key,
+        has_one = proposer,
+        close = proposer,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    /// The approver who proposed the change, refunded for the proposal
+    /// CHECK: checked by has_one
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// The merkle roots of the project, created on first use
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = RootRegistry::LEN,
+        seeds = [
+            b"roots",
+            staking.key.as_ref()
+        ],
+        bump
+    )]
+    pub root_registry: Box<Account<'info, RootRegistry>>,
+
+    /// Anyone paying for the root registry and new rarity accounts if needed
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+
+    /// The System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Executes an approved change of the root registry or of rarities. Rarity accounts are passed
+/// as remaining accounts, like `set_rarities`. Anyone can call it
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteAllowlistProposal<'info>>,
+) -> Result<()> {
+
+    let staking = &mut ctx.accounts.staking;
+    let proposal = &ctx.accounts.proposal;
+    let now = ctx.accounts.clock.unix_timestamp;
+    proposal.check_approved(staking)?;
+
+    let registry = &mut ctx.accounts.root_registry;
+    registry.bump = *ctx.bumps.get("root_registry").unwrap();
+    registry.key = staking.key;
+
+    match &proposal.action {
+        ProposedAction::AddRoot { name, root } => {
+            add_root::add_root(staking, registry, *name, *root)?;
+        }
+        ProposedAction::RetireRoot { index } => {
+            retire_root::retire_root(staking, registry, *index, now)?;
+        }
+        ProposedAction::SetRarities { rarities } => {
+            set_rarities::write_rarities(
+                staking,
+                ctx.program_id,
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                rarities,
+                ctx.remaining_accounts,
+            )?;
+        }
+        _ => return err!(StakingError::InvalidProposal),
+    }
+
+    msg!("Proposal executed");
+
+    Ok(())
+}
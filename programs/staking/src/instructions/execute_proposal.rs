@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, Proposal, ProposedAction};
+use crate::errors::StakingError;
+use super::{set_collection, set_creator, set_rarity_authority, set_reward_groups, update_staking};
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    /// The Staking state account
+    #[account(mut)]
+    pub staking: Account<'info, Staking>,
+
+    /// The approved proposal, closed once executed
+    #[account(
+        mut,
+        constraint = proposal.key == staking.key,
+        has_one = proposer,
+        close = proposer,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    /// The approver who proposed the action, refunded for the proposal
+    /// CHECK: checked by has_one
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Executes an approved staking update, change of the approvers, of the metadata rules, of
+/// the rarity authority or of the reward groups. Withdrawals are executed with `execute_withdrawal`, registry and
+/// rarity changes with `execute_allowlist_proposal`. Anyone can call it
+pub fn handler(ctx: Context<ExecuteProposal>) -> Result<()> {
+
+    let staking = &mut ctx.accounts.staking;
+    let proposal = &ctx.accounts.proposal;
+    let now = ctx.accounts.clock.unix_timestamp;
+    proposal.check_approved(staking)?;
+
+    match &proposal.action {
+        ProposedAction::UpdateStaking { update } => {
+            update_staking::apply_update(staking, update, now)?;
+        }
+        ProposedAction::SetApprovers { approvers, approval_threshold, approval_limit } => {
+            staking.set_approvers(approvers.clone(), *approval_threshold, *approval_limit)?;
+            msg!("Approvers set: {} of {}", staking.approval_threshold, staking.approvers.len());
+        }
+        ProposedAction::SetCollection { collection, collection_rarity } => {
+            set_collection::set_collection(staking, *collection, *collection_rarity)?;
+        }
+        ProposedAction::SetCreator { creator, creator_rarity } => {
+            set_creator::set_creator(staking, *creator, *creator_rarity)?;
+        }
+        ProposedAction::SetRarityAuthority { rarity_authority } => {
+            set_rarity_authority::set_rarity_authority(staking, *rarity_authority);
+        }
+        ProposedAction::SetRewardGroups { reward_groups } => {
+            set_reward_groups::apply_reward_groups(staking, reward_groups, now)?;
+        }
+        ProposedAction::WithdrawRewards { .. }
+        | ProposedAction::AddRoot { .. }
+        | ProposedAction::RetireRoot { .. }
+        | ProposedAction::SetRarities { .. } => return err!(StakingError::InvalidProposal),
+    }
+
+    msg!("Proposal executed");
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, Proposal, ProposedAction};
+use crate::errors::StakingError;
+use crate::token_interface;
+use super::withdraw_rewards;
+
+#[derive(Accounts)]
+pub struct ExecuteWithdrawal<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        seeds = [
+            b"staking",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.staking,
+        has_one = rewards_account,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The approved withdrawal, closed once executed
+    #[account(
+        mut,
+        constraint = proposal.key == staking.key,
+        has_one = proposer,
+        close = proposer,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    /// The approver who proposed the withdrawal, refunded for the proposal
+    /// CHECK: checked by has_one
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// The account holding staking tokens, staking rewards and community funds
+    #[account(
+        seeds = [
+            b"escrow",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.escrow
+    )]
+    /// CHECK: PDA checked by seeds
+    pub escrow: UncheckedAccount<'info>,
+
+    /// The mint of the rewards token
+    /// CHECK: checked by the token program on transfer
+    pub mint: UncheckedAccount<'info>,
+
+    /// The account that holds the rewards token
+    #[account(mut)]
+    /// CHECK: token account of the reward mint, read through the token interface
+    pub rewards_account: UncheckedAccount<'info>,
+
+    /// The token account the proposal withdraws to
+    #[account(mut)]
+    /// CHECK: checked against the proposal in the handler
+    pub destination: UncheckedAccount<'info>,
+
+    /// The program for interacting with the reward token, legacy or Token-2022
+    #[account(constraint = token_interface::is_token_program(token_program.key) @ StakingError::InvalidAccount)]
+    /// CHECK: checked by constraint
+    pub token_program: UncheckedAccount<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Executes an approved withdrawal of rewards. Anyone can call it
+pub fn handler(ctx: Context<ExecuteWithdrawal>) -> Result<()> {
+
+    let staking = &mut ctx.accounts.staking;
+    let proposal = &ctx.accounts.proposal;
+    proposal.check_approved(staking)?;
+
+    let (amount, sunset) = match proposal.action {
+        ProposedAction::WithdrawRewards { amount, sunset, destination }
+            if destination == ctx.accounts.destination.key() => (amount, sunset),
+        _ => return err!(StakingError::InvalidProposal),
+    };
+
+    withdraw_rewards::withdraw(
+        staking,
+        &ctx.accounts.escrow,
+        &ctx.accounts.mint,
+        &ctx.accounts.rewards_account,
+        &ctx.accounts.destination,
+        &ctx.accounts.token_program,
+        amount,
+        sunset,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+
+    msg!("Proposal executed");
+
+    Ok(())
+}
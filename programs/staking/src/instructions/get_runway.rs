@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::Staking;
+use crate::token_interface;
+
+#[derive(Accounts)]
+pub struct GetRunway<'info> {
+    /// The Staking state account
+    #[account(
+        seeds = [
+            b"staking",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.staking,
+        has_one = rewards_account,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The account that holds the rewards token
+    /// CHECK: token account of the reward mint, read through the token interface
+    pub rewards_account: UncheckedAccount<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Returns the days until the vault runs dry at the current emission, within the end and the
+/// campaign windows. u64::MAX if it never does. Read only: meant to be simulated
+pub fn handler(ctx: Context<GetRunway>) -> Result<u64> {
+    let now = ctx.accounts.clock.unix_timestamp;
+    let mut staking = (*ctx.accounts.staking).clone();
+    staking.accrue(now)?;
+
+    let runway_days = staking.runway_days(token_interface::amount(&ctx.accounts.rewards_account)?, now)?;
+
+    msg!(
+        "Liability: {}, daily emission: {}, runway days: {:?}",
+        staking.liability(),
+        staking.daily_emission(now)?,
+        runway_days
+    );
+
+    Ok(runway_days.unwrap_or(u64::MAX))
+}
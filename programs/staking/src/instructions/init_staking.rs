@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount};
 
-use crate::{Staking, InitializeStakingBumps};
+use crate::{Staking, InitializeStakingBumps, RewardMode};
+use crate::errors::StakingError;
+use crate::token_interface;
 
 #[derive(Accounts)]
 #[instruction(bumps: InitializeStakingBumps)]
@@ -15,7 +16,7 @@ pub struct InitializeStaking<'info> {
     #[account(
         init,
         payer = owner,
-        space = 8 + 32 + 32 + 3 + 32 + 32 + 32 + 8 + 8 + 8 + 32 + 1,
+        space = Staking::LEN,
         seeds = [
             b"staking",
             staking_key.key().as_ref()
@@ -35,32 +36,33 @@ pub struct InitializeStaking<'info> {
     /// CHECK: TBD
     pub escrow: AccountInfo<'info>,
 
-    /// The mint of the staking reward token
+    /// The mint of the staking reward token, owned by either token program
+    #[account(owner = token_program.key())]
     /// CHECK: TBD
     pub mint: AccountInfo<'info>,
 
-    /// The account that will hold the rewards token
+    /// The account that will hold the rewards token, created by the handler
+    /// with the extensions the mint requires, even if its address was funded beforehand
     #[account(
-        init,
-        payer = owner,
+        mut,
         seeds = [
             b"rewards",
             staking_key.key().as_ref(),
             mint.key().as_ref()
         ],
-        bump,
-        token::mint = mint,
-        token::authority = escrow
+        bump
     )]
-    pub rewards_account: Account<'info, TokenAccount>,
+    /// CHECK: created in handler
+    pub rewards_account: UncheckedAccount<'info>,
 
     /// The wallet owning the staking program
     #[account(mut)]
     pub owner: Signer<'info>,
 
-    /// The program for interacting with the token.
-    #[account(address = token::ID)]
-    pub token_program: Program<'info, Token>,
+    /// The program for interacting with the token, legacy or Token-2022
+    #[account(constraint = token_interface::is_token_program(token_program.key) @ StakingError::InvalidAccount)]
+    /// CHECK: checked by constraint
+    pub token_program: UncheckedAccount<'info>,
 
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
@@ -73,9 +75,31 @@ pub fn handler(
     daily_rewards: u64,
     start: i64,
     root: [u8; 32],
+    reward_mode: RewardMode,
+    end: Option<i64>,
 ) -> Result<()> {
     msg!("Init Staking");
 
+    if end.is_some_and(|end| end <= start) {
+        return err!(StakingError::InvalidSchedule);
+    }
+
+    let rewards_seeds = &[
+        b"rewards".as_ref(),
+        ctx.accounts.staking_key.key.as_ref(),
+        ctx.accounts.mint.key.as_ref(),
+        &[*ctx.bumps.get("rewards_account").unwrap()],
+    ];
+    token_interface::create_account(
+        &ctx.accounts.token_program,
+        &ctx.accounts.rewards_account,
+        &ctx.accounts.mint,
+        &ctx.accounts.escrow,
+        &ctx.accounts.owner,
+        &ctx.accounts.system_program,
+        &[&rewards_seeds[..]],
+    )?;
+
     let staking = &mut ctx.accounts.staking;
     staking.key = ctx.accounts.staking_key.key();
     staking.owner = ctx.accounts.owner.key();
@@ -85,8 +109,11 @@ pub fn handler(
     staking.rewards_account = ctx.accounts.rewards_account.key();
     staking.daily_rewards = daily_rewards;
     staking.start = start;
+    staking.end = end;
     staking.root = root;
     staking.fees_exempt = false;
+    staking.reward_mode = reward_mode;
+    staking.last_update = start;
 
     msg!("Staking initialized");
 
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::{Staking, StakedNft};
+use crate::errors::StakingError;
+use crate::rewards;
+use super::migrate_staking::resize_account;
+
+#[derive(Accounts)]
+pub struct MigrateStakedNft<'info> {
+    /// The Staking state account, already migrated
+    #[account(
+        mut,
+        seeds = [
+            b"staking",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.staking
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+
+    /// The account representing the staked NFT, possibly using a previous layout
+    /// CHECK: discriminator and project are checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub staked_nft: UncheckedAccount<'info>,
+
+    /// Anyone can pay for the migration of a staked NFT
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Resizes the StakedNft account to the current layout and registers its weight in the project
+pub fn handler(ctx: Context<MigrateStakedNft>) -> Result<()> {
+    let info = ctx.accounts.staked_nft.to_account_info();
+    if info.try_borrow_data()?[..8] != StakedNft::discriminator() {
+        return err!(StakingError::InvalidAccount);
+    }
+
+    resize_account(
+        &info,
+        StakedNft::LEN,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    let mut staked_nft: Account<StakedNft> = Account::try_from(&info)?;
+    if staked_nft.key != ctx.accounts.staking.key {
+        return err!(StakingError::InvalidAccount);
+    }
+
+    rewards::register_migrated_stake(
+        &mut ctx.accounts.staking,
+        &mut staked_nft,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+    staked_nft.exit(&crate::ID)?;
+
+    Ok(())
+}
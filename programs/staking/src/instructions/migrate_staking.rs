@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+
+use crate::Staking;
+use crate::errors::StakingError;
+
+#[derive(Accounts)]
+pub struct MigrateStaking<'info> {
+    /// The Staking state account, possibly using a previous layout
+    /// CHECK: discriminator and owner are checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub staking: UncheckedAccount<'info>,
+
+    /// The wallet that owns the staking
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows an account owned by the program to `len` bytes, zero filling the new fields
+pub fn resize_account<'info>(
+    account: &AccountInfo<'info>,
+    len: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    if account.data_len() >= len {
+        msg!("Account already migrated");
+        return Ok(());
+    }
+
+    let rent = Rent::get()?.minimum_balance(len);
+    if rent > account.lamports() {
+        let cpi_context = CpiContext::new(
+            system_program.clone(),
+            system_program::Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            });
+        system_program::transfer(cpi_context, rent - account.lamports())?;
+    }
+    account.realloc(len, true)?;
+
+    msg!("Account resized to {} bytes", len);
+
+    Ok(())
+}
+
+/// Resizes the Staking account to the current layout
+pub fn handler(ctx: Context<MigrateStaking>) -> Result<()> {
+    {
+        let data = ctx.accounts.staking.try_borrow_data()?;
+        if data.len() < 8 + 32 + 32 || data[..8] != Staking::discriminator() {
+            return err!(StakingError::InvalidAccount);
+        }
+
+        // The owner is stored right after the discriminator and the key
+        if data[40..72] != ctx.accounts.owner.key().to_bytes() {
+            return err!(anchor_lang::error::ErrorCode::ConstraintHasOne);
+        }
+    }
+
+    resize_account(
+        &ctx.accounts.staking.to_account_info(),
+        Staking::LEN,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )
+}
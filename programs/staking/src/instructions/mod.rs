@@ -1,25 +1,103 @@
 pub mod init_staking;
 pub mod set_staking;
+pub mod update_staking;
+pub mod propose_owner;
+pub mod accept_owner;
+pub mod set_role;
+pub mod set_approvers;
+pub mod create_proposal;
+pub mod approve_proposal;
+pub mod execute_proposal;
+pub mod execute_withdrawal;
+pub mod execute_allowlist_proposal;
+pub mod set_staking_windows;
+pub mod set_lock_tiers;
+pub mod set_loyalty;
+pub mod set_early_unstake_penalty;
+pub mod set_token_weights;
+pub mod set_collection;
+pub mod set_creator;
+pub mod add_root;
+pub mod retire_root;
+pub mod set_rarity_authority;
+pub mod set_revalidation_grace;
+pub mod set_rarities;
+pub mod set_reward_groups;
 pub mod set_fees_exempt;
 pub mod withdraw_rewards;
+pub mod get_runway;
 pub mod stake_nft;
+pub mod unstake_nft;
+pub mod stake_freeze;
+pub mod unstake_freeze;
+pub mod stake_cnft;
+pub mod unstake_cnft;
+pub mod stake_core;
+pub mod unstake_core;
 pub mod stake_ocp;
 pub mod unstake_ocp;
 pub mod stake_mpl;
 pub mod unstake_mpl;
 pub mod unstake_mpl_custodial;
+pub mod stake_tokens;
+pub mod unstake_tokens;
+pub mod claim_position;
 pub mod claim_staking;
+pub mod refresh_rarity;
+pub mod revalidate_stake;
+pub mod claim_owed_rewards;
+pub mod migrate_staking;
+pub mod migrate_staked_nft;
 //pub mod migrate_escrow;
 
 pub use init_staking::*;
 pub use set_staking::*;
+pub use update_staking::*;
+pub use propose_owner::*;
+pub use accept_owner::*;
+pub use set_role::*;
+pub use set_approvers::*;
+pub use create_proposal::*;
+pub use approve_proposal::*;
+pub use execute_proposal::*;
+pub use execute_withdrawal::*;
+pub use execute_allowlist_proposal::*;
+pub use set_staking_windows::*;
+pub use set_lock_tiers::*;
+pub use set_loyalty::*;
+pub use set_early_unstake_penalty::*;
+pub use set_token_weights::*;
+pub use set_collection::*;
+pub use set_creator::*;
+pub use add_root::*;
+pub use retire_root::*;
+pub use set_rarity_authority::*;
+pub use set_revalidation_grace::*;
+pub use set_rarities::*;
+pub use set_reward_groups::*;
 pub use set_fees_exempt::*;
 pub use withdraw_rewards::*;
+pub use get_runway::*;
 pub use stake_nft::*;
+pub use unstake_nft::*;
+pub use stake_freeze::*;
+pub use unstake_freeze::*;
+pub use stake_cnft::*;
+pub use unstake_cnft::*;
+pub use stake_core::*;
+pub use unstake_core::*;
 pub use stake_ocp::*;
 pub use unstake_ocp::*;
 pub use stake_mpl::*;
 pub use unstake_mpl::*;
 pub use unstake_mpl_custodial::*;
+pub use stake_tokens::*;
+pub use unstake_tokens::*;
+pub use claim_position::*;
 pub use claim_staking::*;
+pub use refresh_rarity::*;
+pub use revalidate_stake::*;
+pub use claim_owed_rewards::*;
+pub use migrate_staking::*;
+pub use migrate_staked_nft::*;
 //pub use migrate_escrow::*;
\ No newline at end of file
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::Staking;
+
+#[derive(Accounts)]
+pub struct ProposeOwner<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        has_one = owner,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The wallet that owns the staking
+    pub owner: Signer<'info>,
+}
+
+/// Proposes `pending_owner` as the new owner. Ownership only moves once it accepts,
+/// so a wrong key can't orphan the project
+pub fn handler(
+    ctx: Context<ProposeOwner>,
+    pending_owner: Pubkey,
+) -> Result<()> {
+
+    let staking = &mut ctx.accounts.staking;
+    staking.pending_owner = pending_owner;
+
+    msg!("Owner proposed: {}", staking.pending_owner);
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, StakedNft, OwedRewards, Rarity};
+use crate::rewards;
+use crate::errors::StakingError;
+use crate::token_interface;
+
+#[derive(Accounts)]
+pub struct RefreshRarity<'info> {
+    /// The staking state
+    #[account(
+        mut,
+        seeds = [
+            b"staking",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.staking,
+        has_one = mint,
+        has_one = rewards_account
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+
+    /// The account holding staking tokens, staking rewards and community funds
+    #[account(
+        mut,
+        seeds = [
+            b"escrow",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.escrow
+    )]
+    /// CHECK: TBD
+    pub escrow: AccountInfo<'info>,
+
+    /// The account representing the staked NFT
+    #[account(
+        mut,
+        seeds = [
+            b"staked_nft".as_ref(),
+            staked_nft.mint.as_ref()
+        ],
+        bump = staked_nft.bumps.staked_nft,
+        has_one = staker,
+        constraint = staked_nft.key == staking.key @ StakingError::InvalidAccount
+    )]
+    pub staked_nft: Box<Account<'info, StakedNft>>,
+
+    /// The rarity of the staked NFT in the project rarity registry
+    #[account(
+        seeds = [
+            b"rarity",
+            staking.key.as_ref(),
+            staked_nft.mint.as_ref()
+        ],
+        bump = rarity.bump
+    )]
+    pub rarity: Box<Account<'info, Rarity>>,
+
+    /// The owner of the staked token
+    /// CHECK: checked by the staked NFT
+    pub staker: UncheckedAccount<'info>,
+
+    /// Anyone paying for the owed rewards account if needed
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The mint of the reward token
+    #[account(mut)]
+    /// CHECK: TBD
+    pub mint: AccountInfo<'info>,
+
+    /// The staker account receiving rewards
+    #[account(
+        mut, 
+        constraint = token_interface::is_account_of(&staker_account, &mint.key(), &staker.key())
+    )]
+    /// CHECK: checked by constraint
+    pub staker_account: UncheckedAccount<'info>,
+
+    /// The rewards owed to the staker when the vault could not pay in full
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = OwedRewards::LEN,
+        seeds = [
+            b"owed",
+            staking.key.as_ref(),
+            staker.key().as_ref()
+        ],
+        bump
+    )]
+    pub owed_rewards: Box<Account<'info, OwedRewards>>,
+
+    /// The account that will hold the rewards token
+    #[account(
+        mut,
+        seeds = [
+            b"rewards",
+            staking.key.as_ref(),
+            staking.mint.as_ref()
+        ],
+        bump = staking.bumps.rewards,
+    )]
+    /// CHECK: token account of the reward mint, read through the token interface
+    pub rewards_account: UncheckedAccount<'info>,
+
+    /// The program for interacting with the reward token, legacy or Token-2022
+    #[account(constraint = token_interface::is_token_program(token_program.key) @ StakingError::InvalidAccount)]
+    /// CHECK: checked by constraint
+    pub token_program: UncheckedAccount<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Pays the rewards of a staked NFT at its current rarity, then moves it to the rarity
+/// of the registry
+pub fn handler(ctx: Context<RefreshRarity>) -> Result<()> {
+    let staking = &mut ctx.accounts.staking;
+    let staked_nft = &mut ctx.accounts.staked_nft;
+    let now = ctx.accounts.clock.unix_timestamp;
+
+    if ctx.accounts.rarity.rarity_multiplier == 0 {
+        return err!(StakingError::NotInRarityRegistry);
+    }
+
+    // Settle at the previous rarity
+    let rewards_amount = rewards::settle_rewards(staking, staked_nft, now)?;
+    rewards::set_rarity(staking, staked_nft, ctx.accounts.rarity.rarity_multiplier, now)?;
+    staked_nft.validated_at = now;
+
+    // Rewards owed to the staker, created on first use
+    let amount_paid = rewards::pay_or_owe_staker(
+        staking,
+        &mut ctx.accounts.owed_rewards,
+        *ctx.bumps.get("owed_rewards").unwrap(),
+        ctx.accounts.staker.key(),
+        rewards_amount,
+        token_interface::amount(&ctx.accounts.rewards_account)?,
+    )?;
+
+    let seeds = &[
+        b"escrow".as_ref(),
+        staking.key.as_ref(),
+        &[staking.bumps.escrow],
+    ];
+    let signer = &[&seeds[..]];
+
+    if amount_paid > 0 {
+        let received = token_interface::transfer_checked(
+            &ctx.accounts.token_program,
+            &ctx.accounts.rewards_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.staker_account,
+            &ctx.accounts.escrow,
+            amount_paid,
+            signer,
+        )?;
+        msg!("Rewards paid: {}", received);
+    }
+
+    msg!("Rarity refreshed: {}", staked_nft.rarity_multiplier);
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, RootRegistry};
+use crate::errors::StakingError;
+use crate::roles;
+
+#[derive(Accounts)]
+pub struct RetireRoot<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        constraint = staking.can(owner.key, roles::ALLOWLIST) @ StakingError::Unauthorized,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The merkle roots of the project
+    #[account(
+        mut,
+        seeds = [
+            b"roots",
+            staking.key.as_ref()
+        ],
+        bump = root_registry.bump
+    )]
+    pub root_registry: Box<Account<'info, RootRegistry>>,
+
+    /// The owner of the staking, or a wallet with the allowlist permission
+    pub owner: Signer<'info>,
+}
+
+/// Retires a merkle root of the registry. Once approvals are set, it goes through a proposal
+pub fn handler(
+    ctx: Context<RetireRoot>,
+    index: u8,
+) -> Result<()> {
+
+    if ctx.accounts.staking.uses_approvals() {
+        return err!(StakingError::NeedsApproval);
+    }
+
+    retire_root(
+        &mut ctx.accounts.staking,
+        &mut ctx.accounts.root_registry,
+        index,
+        Clock::get()?.unix_timestamp,
+    )
+}
+
+/// Retires a merkle root of the registry. NFTs already staked through it stay staked, but
+/// staked NFTs have to revalidate against the remaining roots as after a root change.
+/// Its reward group loses its root, so a root added in the slot doesn't join the group
+pub fn retire_root(staking: &mut Staking, registry: &mut RootRegistry, index: u8, now: i64) -> Result<()> {
+    let named_root = match registry.roots.get_mut(index as usize) {
+        Some(root) if root.active => root,
+        _ => return err!(StakingError::InvalidRootIndex),
+    };
+    named_root.active = false;
+    named_root.root = [0; 32];
+
+    staking.root_updated_at = now;
+    for group in staking.reward_groups.iter_mut().filter(|group| group.root_index == Some(index)) {
+        group.root_index = None;
+    }
+
+    msg!("Root retired: {}", index);
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::Staking;
+use crate::errors::StakingError;
+
+#[derive(Accounts)]
+pub struct SetApprovers<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        has_one = owner,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The wallet that owns the staking
+    pub owner: Signer<'info>,
+}
+
+/// Sets the first approvers of the project. Once set, they can only change through a proposal
+pub fn handler(
+    ctx: Context<SetApprovers>,
+    approvers: Vec<Pubkey>,
+    approval_threshold: u8,
+    approval_limit: u64,
+) -> Result<()> {
+
+    let staking = &mut ctx.accounts.staking;
+    if staking.uses_approvals() {
+        return err!(StakingError::NeedsApproval);
+    }
+
+    staking.set_approvers(approvers, approval_threshold, approval_limit)?;
+
+    msg!("Approvers set: {} of {}", staking.approval_threshold, staking.approvers.len());
+
+    Ok(())
+}
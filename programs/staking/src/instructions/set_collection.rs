@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::Staking;
+use crate::errors::StakingError;
+use crate::roles;
+
+#[derive(Accounts)]
+pub struct SetCollection<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        constraint = staking.can(owner.key, roles::ALLOWLIST) @ StakingError::Unauthorized,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The owner of the staking, or a wallet with the allowlist permission
+    pub owner: Signer<'info>,
+}
+
+/// Sets the collection rule. Once approvals are set, it goes through a proposal
+pub fn handler(
+    ctx: Context<SetCollection>,
+    collection: Pubkey,
+    collection_rarity: u64,
+) -> Result<()> {
+
+    let staking = &mut ctx.accounts.staking;
+    if staking.uses_approvals() {
+        return err!(StakingError::NeedsApproval);
+    }
+
+    set_collection(staking, collection, collection_rarity)
+}
+
+/// Sets the verified collection NFTs are checked against and their rarity multiplier.
+/// NFTs already staked keep the rarity they were staked with
+pub fn set_collection(staking: &mut Staking, collection: Pubkey, collection_rarity: u64) -> Result<()> {
+    // Collection NFTs need a rarity, the allowlist carries its own
+    if collection != Pubkey::default() && collection_rarity == 0 {
+        return err!(StakingError::InvalidRuleRarity);
+    }
+
+    staking.collection = collection;
+    staking.collection_rarity = collection_rarity;
+
+    msg!("Collection set: {}", staking.collection);
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::Staking;
+use crate::errors::StakingError;
+use crate::roles;
+
+#[derive(Accounts)]
+pub struct SetCreator<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        constraint = staking.can(owner.key, roles::ALLOWLIST) @ StakingError::Unauthorized,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The owner of the staking, or a wallet with the allowlist permission
+    pub owner: Signer<'info>,
+}
+
+/// Sets the creator rule. Once approvals are set, it goes through a proposal
+pub fn handler(
+    ctx: Context<SetCreator>,
+    creator: Pubkey,
+    creator_rarity: u64,
+) -> Result<()> {
+
+    let staking = &mut ctx.accounts.staking;
+    if staking.uses_approvals() {
+        return err!(StakingError::NeedsApproval);
+    }
+
+    set_creator(staking, creator, creator_rarity)
+}
+
+/// Sets the verified first creator NFTs can be accepted through and their rarity multiplier.
+/// NFTs already staked keep the rarity they were staked with
+pub fn set_creator(staking: &mut Staking, creator: Pubkey, creator_rarity: u64) -> Result<()> {
+    // Creator NFTs need a rarity, the allowlist carries its own
+    if creator != Pubkey::default() && creator_rarity == 0 {
+        return err!(StakingError::InvalidRuleRarity);
+    }
+
+    staking.creator = creator;
+    staking.creator_rarity = creator_rarity;
+
+    msg!("Creator set: {}", staking.creator);
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, EarlyUnstakePenalty};
+use crate::errors::StakingError;
+use crate::token_interface;
+use crate::roles;
+
+#[derive(Accounts)]
+pub struct SetEarlyUnstakePenalty<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        constraint = staking.can(owner.key, roles::CONFIG) @ StakingError::Unauthorized,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The owner of the staking, or a wallet with the config permission
+    pub owner: Signer<'info>,
+}
+
+/// Sets the early unstake penalty.
+/// A treasury must be passed as the first remaining account and hold the reward token
+pub fn handler(
+    ctx: Context<SetEarlyUnstakePenalty>,
+    penalty: EarlyUnstakePenalty,
+) -> Result<()> {
+
+    if penalty.min_duration < 0 || penalty.rate > 100 {
+        return err!(StakingError::InvalidPenalty);
+    }
+
+    let staking = &mut ctx.accounts.staking;
+
+    if penalty.treasury != Pubkey::default() {
+        let treasury = ctx.remaining_accounts
+            .first()
+            .filter(|account| account.key() == penalty.treasury)
+            .ok_or_else(|| error!(StakingError::InvalidAccount))?;
+        if token_interface::mint(treasury)? != staking.mint {
+            return err!(StakingError::InvalidAccount);
+        }
+    }
+
+    staking.early_unstake_penalty = penalty;
+
+    msg!("Early unstake penalty set: {}% before {}s", penalty.rate, penalty.min_duration);
+
+    Ok(())
+}
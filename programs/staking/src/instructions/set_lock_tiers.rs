@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, LockTier};
+use crate::errors::StakingError;
+use crate::roles;
+
+#[derive(Accounts)]
+pub struct SetLockTiers<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        constraint = staking.can(owner.key, roles::CONFIG) @ StakingError::Unauthorized,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The owner of the staking, or a wallet with the config permission
+    pub owner: Signer<'info>,
+}
+
+/// Sets the lock tiers stakers can commit their NFTs to.
+/// NFTs already staked keep the lock and boost they committed to
+pub fn handler(
+    ctx: Context<SetLockTiers>,
+    lock_tiers: Vec<LockTier>,
+) -> Result<()> {
+
+    if lock_tiers.len() > Staking::MAX_LOCK_TIERS
+        || lock_tiers.iter().any(|tier| tier.duration <= 0) {
+        return err!(StakingError::InvalidLockTier);
+    }
+
+    let staking = &mut ctx.accounts.staking;
+    staking.lock_tiers = lock_tiers;
+
+    msg!("Lock tiers set: {}", staking.lock_tiers.len());
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, LoyaltyCurve};
+use crate::errors::StakingError;
+use crate::roles;
+
+#[derive(Accounts)]
+pub struct SetLoyalty<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        constraint = staking.can(owner.key, roles::CONFIG) @ StakingError::Unauthorized,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The owner of the staking, or a wallet with the config permission
+    pub owner: Signer<'info>,
+}
+
+/// Sets the loyalty curve. Rewards are accrued first: the time already elapsed keeps the weight
+/// registered under the previous curve, each stake moves to the new one on its next claim
+pub fn handler(
+    ctx: Context<SetLoyalty>,
+    loyalty: LoyaltyCurve,
+) -> Result<()> {
+
+    loyalty.validate()?;
+
+    let staking = &mut ctx.accounts.staking;
+    staking.accrue(Clock::get()?.unix_timestamp)?;
+    staking.loyalty = loyalty;
+
+    msg!("Loyalty set: +{}% every {}s up to +{}%", loyalty.step_boost, loyalty.step_duration, loyalty.max_boost);
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, Rarity, MintRarity};
+use crate::errors::StakingError;
+use crate::roles;
+use crate::token_interface;
+
+#[derive(Accounts)]
+pub struct SetRarities<'info> {
+    /// The Staking state account
+    #[account(constraint = staking.can(owner.key, roles::ALLOWLIST) @ StakingError::Unauthorized)]
+    pub staking: Account<'info, Staking>,
+
+    /// The owner of the staking, or a wallet with the allowlist permission, paying for new rarity accounts
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Writes the rarity multiplier of each mint to its `Rarity` account, passed in the same order
+/// as remaining accounts. Once approvals are set, it goes through a proposal
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetRarities<'info>>,
+    rarities: Vec<MintRarity>,
+) -> Result<()> {
+
+    if ctx.accounts.staking.uses_approvals() {
+        return err!(StakingError::NeedsApproval);
+    }
+
+    write_rarities(
+        &ctx.accounts.staking,
+        ctx.program_id,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &rarities,
+        ctx.remaining_accounts,
+    )
+}
+
+/// Writes the rarity multiplier of each mint to its `Rarity` account in `accounts`, created
+/// by `payer` on first write. Staked NFTs keep their rarity until `refresh_rarity` is called
+pub fn write_rarities<'info>(
+    staking: &Staking,
+    program_id: &Pubkey,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    rarities: &[MintRarity],
+    accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    if rarities.len() != accounts.len() {
+        return err!(StakingError::InvalidAccount);
+    }
+
+    let key = staking.key;
+    for (entry, account) in rarities.iter().zip(accounts.iter()) {
+        let (address, bump) = Pubkey::find_program_address(
+            &[b"rarity", key.as_ref(), entry.mint.as_ref()],
+            program_id,
+        );
+        if account.key() != address {
+            return err!(StakingError::InvalidAccount);
+        }
+
+        // Create the rarity account on first write, lamports sent to it beforehand are kept
+        if account.data_is_empty() {
+            token_interface::create_pda(
+                account,
+                Rarity::LEN,
+                program_id,
+                payer,
+                system_program,
+                &[&[b"rarity", key.as_ref(), entry.mint.as_ref(), &[bump]]],
+            )?;
+        } else if account.owner != program_id {
+            return err!(StakingError::InvalidAccount);
+        }
+
+        let rarity = Rarity {
+            bump,
+            key,
+            mint: entry.mint,
+            rarity_multiplier: entry.rarity_multiplier,
+        };
+        let mut data = account.try_borrow_mut_data()?;
+        rarity.try_serialize(&mut &mut data[..])?;
+    }
+
+    msg!("Rarities set: {}", rarities.len());
+
+    Ok(())
+}
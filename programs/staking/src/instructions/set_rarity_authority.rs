@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::Staking;
+use crate::errors::StakingError;
+use crate::roles;
+
+#[derive(Accounts)]
+pub struct SetRarityAuthority<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        constraint = staking.can(owner.key, roles::ALLOWLIST) @ StakingError::Unauthorized,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The owner of the staking, or a wallet with the allowlist permission
+    pub owner: Signer<'info>,
+}
+
+/// Sets the key signing rarity attestations. Once approvals are set, it goes through a proposal
+pub fn handler(
+    ctx: Context<SetRarityAuthority>,
+    rarity_authority: Pubkey,
+) -> Result<()> {
+
+    let staking = &mut ctx.accounts.staking;
+    if staking.uses_approvals() {
+        return err!(StakingError::NeedsApproval);
+    }
+
+    set_rarity_authority(staking, rarity_authority);
+
+    Ok(())
+}
+
+/// Sets the key signing rarity attestations.
+/// Attestations already signed by the previous key are no longer accepted
+pub fn set_rarity_authority(staking: &mut Staking, rarity_authority: Pubkey) {
+    staking.rarity_authority = rarity_authority;
+
+    msg!("Rarity authority set: {}", staking.rarity_authority);
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::Staking;
+use crate::errors::StakingError;
+use crate::roles;
+
+#[derive(Accounts)]
+pub struct SetRevalidationGrace<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        constraint = staking.can(owner.key, roles::CONFIG) @ StakingError::Unauthorized,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The owner of the staking, or a wallet with the config permission
+    pub owner: Signer<'info>,
+}
+
+/// Sets the time staked NFTs have to revalidate after a root change.
+/// Applies to the current root change as well
+pub fn handler(
+    ctx: Context<SetRevalidationGrace>,
+    revalidation_grace: i64,
+) -> Result<()> {
+
+    if revalidation_grace < 0 {
+        return err!(StakingError::InvalidRevalidationGrace);
+    }
+
+    let staking = &mut ctx.accounts.staking;
+    staking.revalidation_grace = revalidation_grace;
+
+    msg!("Revalidation grace set: {}", staking.revalidation_grace);
+
+    Ok(())
+}
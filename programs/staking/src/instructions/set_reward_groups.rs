@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, RewardGroup};
+use crate::errors::StakingError;
+use crate::roles;
+
+#[derive(Accounts)]
+pub struct SetRewardGroups<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        constraint = staking.can(owner.key, roles::CONFIG) @ StakingError::Unauthorized,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The owner of the staking, or a wallet with the config permission. Changing the
+    /// collection or root of a group also needs the allowlist permission
+    pub owner: Signer<'info>,
+}
+
+/// Sets the reward groups of the project. Collection groups change which NFTs are accepted,
+/// so adding or changing the source of a group is an allowlist change, which goes through a
+/// proposal once approvals are set
+pub fn handler(
+    ctx: Context<SetRewardGroups>,
+    reward_groups: Vec<RewardGroup>,
+) -> Result<()> {
+
+    let staking = &mut ctx.accounts.staking;
+    if changes_sources(staking, &reward_groups) {
+        if !staking.can(ctx.accounts.owner.key, roles::ALLOWLIST) {
+            return err!(StakingError::Unauthorized);
+        }
+        if staking.uses_approvals() {
+            return err!(StakingError::NeedsApproval);
+        }
+    }
+
+    apply_reward_groups(staking, &reward_groups, Clock::get()?.unix_timestamp)
+}
+
+/// Returns true if `reward_groups` add, remove or change the collection or root of a group
+pub fn changes_sources(staking: &Staking, reward_groups: &[RewardGroup]) -> bool {
+    reward_groups.len() != staking.reward_groups.len()
+        || reward_groups.iter().zip(staking.reward_groups.iter()).any(|(group, old)| {
+            group.collection != old.collection || group.root_index != old.root_index
+        })
+}
+
+/// Sets the reward groups of the project. A group keeps its index, and its staked NFTs, when
+/// it is edited. Only the cap of a group with staked NFTs can change, as its stakes earn at
+/// its rate since their last claim
+pub fn apply_reward_groups(staking: &mut Staking, reward_groups: &[RewardGroup], now: i64) -> Result<()> {
+    if reward_groups.len() > Staking::MAX_REWARD_GROUPS {
+        return err!(StakingError::InvalidRewardGroups);
+    }
+
+    staking.accrue(now)?;
+
+    // Groups with staked NFTs can't be removed, their stakes point to them
+    if staking.reward_groups[reward_groups.len().min(staking.reward_groups.len())..]
+        .iter()
+        .any(|group| group.nfts_staked != 0)
+    {
+        return err!(StakingError::InvalidRewardGroups);
+    }
+
+    let mut groups = Vec::with_capacity(reward_groups.len());
+    for (i, group) in reward_groups.iter().enumerate() {
+        let nfts_staked = staking.reward_groups.get(i).map_or(0, |old| old.nfts_staked);
+        let is_collection = group.collection != Pubkey::default();
+        if nfts_staked != 0 {
+            // The stakes of the group keep its source and rate
+            let old = &staking.reward_groups[i];
+            if group.collection != old.collection
+                || group.root_index != old.root_index
+                || group.rate != old.rate
+            {
+                return err!(StakingError::InvalidRewardGroups);
+            }
+        } else {
+            // A group is either a collection or a registry root
+            if group.rate == 0 || is_collection == group.root_index.is_some() {
+                return err!(StakingError::InvalidRewardGroups);
+            }
+
+            // Collection NFTs stake with the collection rarity
+            if is_collection && staking.collection_rarity == 0 {
+                return err!(StakingError::InvalidRuleRarity);
+            }
+        }
+
+        // A source is only defined once. Groups of a retired root have none left
+        if (is_collection || group.root_index.is_some())
+            && reward_groups[..i].iter().any(|other| {
+                other.collection == group.collection && other.root_index == group.root_index
+            })
+        {
+            return err!(StakingError::InvalidRewardGroups);
+        }
+
+        groups.push(RewardGroup {
+            nfts_staked,
+            ..*group
+        });
+    }
+
+    staking.reward_groups = groups;
+
+    msg!("Reward groups set: {}", staking.reward_groups.len());
+
+    Ok(())
+}
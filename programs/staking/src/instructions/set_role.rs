@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, Role};
+use crate::errors::StakingError;
+use crate::roles;
+
+#[derive(Accounts)]
+pub struct SetRole<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        has_one = owner,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The wallet that owns the staking
+    pub owner: Signer<'info>,
+}
+
+/// Grants `permissions` to `key`, replacing the ones it had. No permission removes its role
+pub fn handler(
+    ctx: Context<SetRole>,
+    key: Pubkey,
+    permissions: u8,
+) -> Result<()> {
+
+    // The owner already holds every permission
+    if permissions & !roles::ALL != 0 || key == Pubkey::default() || key == ctx.accounts.owner.key() {
+        return err!(StakingError::InvalidRole);
+    }
+
+    let staking = &mut ctx.accounts.staking;
+    let existing = staking.roles.iter().position(|role| role.key == key);
+    match (existing, permissions) {
+        (Some(index), 0) => {
+            staking.roles.remove(index);
+        }
+        (Some(index), _) => staking.roles[index].permissions = permissions,
+        (None, 0) => {}
+        (None, _) => {
+            if staking.roles.len() >= Staking::MAX_ROLES {
+                return err!(StakingError::RoleTableFull);
+            }
+            staking.roles.push(Role { key, permissions });
+        }
+    }
+
+    msg!("Role set: {} {}", key, permissions);
+
+    Ok(())
+}
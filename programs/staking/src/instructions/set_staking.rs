@@ -1,37 +1,51 @@
 use anchor_lang::prelude::*;
 
 use crate::{Staking};
+use crate::errors::StakingError;
+use crate::roles;
 
 #[derive(Accounts)]
 pub struct SetStaking<'info> {
     /// The Staking state account
     #[account(
         mut,
-        has_one = owner,
+        constraint = staking.can(owner.key, roles::CONFIG) @ StakingError::Unauthorized,
     )]
     pub staking: Account<'info, Staking>,
 
-    /// The wallet that owns the staking
+    /// The owner of the staking, or a wallet with the config permission. Changing the root
+    /// also needs the allowlist permission
     pub owner: Signer<'info>,
-    
-    /// The wallet that will own the staking
-    /// CHECK: TBD
-    pub new_owner: AccountInfo<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
 }
 
-/// Sets the staking parameters
+/// Sets the staking parameters. A new root also needs the allowlist permission
 pub fn handler(
     ctx: Context<SetStaking>,
     daily_rewards: u64,
     start: i64,
     root: [u8; 32],
+    end: Option<i64>,
 ) -> Result<()> {
 
     let staking = &mut ctx.accounts.staking;
-    staking.owner = ctx.accounts.new_owner.key();
-    staking.daily_rewards = daily_rewards;
-    staking.start = start;
-    staking.root = root;
+    let now = ctx.accounts.clock.unix_timestamp;
+
+    if root != staking.root && !staking.can(ctx.accounts.owner.key, roles::ALLOWLIST) {
+        return err!(StakingError::Unauthorized);
+    }
+
+    // Root changes go through a proposal once approvals are set
+    if staking.uses_approvals() && root != staking.root {
+        return err!(StakingError::NeedsApproval);
+    }
+
+    staking.set_daily_rewards(daily_rewards, now)?;
+    staking.set_end(end, now)?;
+    staking.set_start(start, now)?;
+    staking.set_root(root, now);
 
     msg!("Staking set");
 
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, StakingWindow};
+use crate::errors::StakingError;
+use crate::roles;
+
+#[derive(Accounts)]
+pub struct SetStakingWindows<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        constraint = staking.can(owner.key, roles::CONFIG) @ StakingError::Unauthorized,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The owner of the staking, or a wallet with the config permission
+    pub owner: Signer<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Sets the campaign windows rewards accrue in
+pub fn handler(
+    ctx: Context<SetStakingWindows>,
+    windows: Vec<StakingWindow>,
+) -> Result<()> {
+
+    let staking = &mut ctx.accounts.staking;
+    staking.set_windows(windows, ctx.accounts.clock.unix_timestamp)?;
+
+    msg!("Staking windows set: {}", staking.windows.len());
+
+    Ok(())
+}
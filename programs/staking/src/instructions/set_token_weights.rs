@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, TokenWeight};
+use crate::errors::StakingError;
+use crate::roles;
+
+#[derive(Accounts)]
+pub struct SetTokenWeights<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        constraint = staking.can(owner.key, roles::CONFIG) @ StakingError::Unauthorized,
+    )]
+    pub staking: Account<'info, Staking>,
+
+    /// The owner of the staking, or a wallet with the config permission
+    pub owner: Signer<'info>,
+}
+
+/// Sets the fungible tokens stakers can deposit and their weight.
+/// Positions already staked move to the new weight the next time they are settled
+pub fn handler(
+    ctx: Context<SetTokenWeights>,
+    token_weights: Vec<TokenWeight>,
+) -> Result<()> {
+
+    if token_weights.len() > Staking::MAX_TOKEN_WEIGHTS {
+        return err!(StakingError::InvalidTokenWeights);
+    }
+
+    // A mint can only be weighted once
+    for (i, token) in token_weights.iter().enumerate() {
+        if token_weights[..i].iter().any(|other| other.mint == token.mint) {
+            return err!(StakingError::InvalidTokenWeights);
+        }
+    }
+
+    let staking = &mut ctx.accounts.staking;
+    staking.token_weights = token_weights;
+
+    msg!("Token weights set: {}", staking.token_weights.len());
+
+    Ok(())
+}
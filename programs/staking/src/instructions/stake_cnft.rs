@@ -0,0 +1,191 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use solana_program::program::invoke;
+
+use crate::{Staking, StakedNft, StakedNftBumps, CompressedLeaf};
+use crate::bubblegum;
+use crate::eligibility;
+use crate::rewards;
+use crate::errors::StakingError;
+use crate::fees_wallet;
+
+#[derive(Accounts)]
+#[instruction(
+    bumps: StakedNftBumps,
+    proof: Vec<[u8; 32]>,
+    rarity_multiplier: u64,
+    lock_tier: Option<u8>,
+    leaf: CompressedLeaf
+)]
+pub struct StakeCnft<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        seeds = [
+            b"staking",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.staking
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+
+    /// The account holding staking tokens, staking rewards and community funds
+    /// Custodies the staked compressed NFTs
+    #[account(
+        seeds = [
+            b"escrow",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.escrow
+    )]
+    /// CHECK: TBD
+    pub escrow: AccountInfo<'info>,
+
+    /// The account representing the staked NFT, keyed by asset id
+    /// Doesn't use staking.key as one token can only be staked once
+    #[account(
+        init,
+        payer = staker,
+        space = StakedNft::LEN,
+        seeds = [
+            b"staked_nft",
+            bubblegum::asset_id(&merkle_tree.key(), leaf.nonce).as_ref()
+        ],
+        bump
+    )]
+    pub staked_nft: Box<Account<'info, StakedNft>>,
+
+    /// The owner of the NFT being staked
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    /// CHECK: checked in cpi, part of the leaf
+    pub leaf_delegate: UncheckedAccount<'info>,
+
+    /// CHECK: checked in cpi
+    pub tree_authority: UncheckedAccount<'info>,
+
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// The fee receiving account
+    #[account(mut, address = fees_wallet::ID)]
+    /// CHECK: TBD
+    pub fee_receiver_account: AccountInfo<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+
+    /// CHECK: checked by address
+    #[account(address = bubblegum::spl_noop::ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: checked by address
+    #[account(address = bubblegum::spl_account_compression::ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: checked by address
+    #[account(address = bubblegum::ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Stakes a compressed NFT, moving it to the escrow.
+/// The nodes of the leaf proof not covered by the tree canopy are passed as remaining accounts,
+/// after the accounts proving eligibility
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, StakeCnft<'info>>,
+    bumps: StakedNftBumps,
+    proof: Vec<[u8; 32]>,
+    rarity_multiplier: u64,
+    lock_tier: Option<u8>,
+    leaf: CompressedLeaf,
+    root_index: Option<u8>,
+) -> Result<()> {
+    let staking = &mut ctx.accounts.staking;
+    let asset_id = bubblegum::asset_id(&ctx.accounts.merkle_tree.key(), leaf.nonce);
+
+    // Check that staking started
+    if staking.start > ctx.accounts.clock.unix_timestamp {
+        return err!(StakingError::TooEarly);
+    }
+
+    // Check that staking did not end
+    if staking.has_ended(ctx.accounts.clock.unix_timestamp) {
+        return err!(StakingError::StakingEnded);
+    }
+
+    // Check the NFT belongs to the project, through the allowlist or the collection
+    // The leaf proof nodes follow the accounts proving eligibility
+    let mut remaining_accounts = ctx.remaining_accounts.iter();
+    let eligible = eligibility::verify_compressed(
+        staking,
+        &asset_id,
+        &mut remaining_accounts,
+        proof,
+        root_index,
+        rarity_multiplier,
+    )?;
+
+    // Charge fees if the project is not fees exempt
+    if !staking.fees_exempt {
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.staker.to_account_info(),
+                to: ctx.accounts.fee_receiver_account.clone(),
+            });
+        system_program::transfer(cpi_context, fees_wallet::FEES_LAMPORTS)?;
+    }
+
+    // Update staking data
+    staking.nfts_staked += 1;
+
+    // Update staked_nft data
+    let staked_nft = &mut ctx.accounts.staked_nft;
+    staked_nft.bumps = bumps;
+    staked_nft.key = staking.key;
+    staked_nft.mint = asset_id;
+    staked_nft.staker = ctx.accounts.staker.key();
+    staked_nft.staked_at = ctx.accounts.clock.unix_timestamp;
+    staked_nft.last_claim = ctx.accounts.clock.unix_timestamp;
+    staked_nft.rarity_multiplier = eligible.rarity_multiplier;
+    staked_nft.group = staking.group_of(&eligible);
+    rewards::lock_stake(staking, staked_nft, lock_tier)?;
+    rewards::register_stake(staking, staked_nft, ctx.accounts.clock.unix_timestamp)?;
+
+    let proof_nodes = remaining_accounts.as_slice();
+
+    // Move the compressed NFT to the escrow, Bubblegum verifies the leaf against the tree
+    let transfer = bubblegum::transfer(
+        ctx.accounts.tree_authority.key(),
+        ctx.accounts.staker.key(),
+        ctx.accounts.leaf_delegate.key(),
+        ctx.accounts.escrow.key(),
+        ctx.accounts.merkle_tree.key(),
+        proof_nodes,
+        &leaf,
+    );
+
+    let mut accounts = vec![
+        ctx.accounts.tree_authority.to_account_info(),
+        ctx.accounts.staker.to_account_info(),
+        ctx.accounts.leaf_delegate.to_account_info(),
+        ctx.accounts.escrow.to_account_info(),
+        ctx.accounts.merkle_tree.to_account_info(),
+        ctx.accounts.log_wrapper.to_account_info(),
+        ctx.accounts.compression_program.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.bubblegum_program.to_account_info(),
+    ];
+    accounts.extend(proof_nodes.iter().cloned());
+
+    invoke(&transfer, &accounts)?;
+
+    msg!("Token staked");
+
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use solana_program::program::invoke;
+
+use crate::{Staking, StakedNft, StakedNftBumps};
+use crate::mpl_core;
+use crate::eligibility;
+use crate::rewards;
+use crate::errors::StakingError;
+use crate::fees_wallet;
+
+#[derive(Accounts)]
+#[instruction(bumps: StakedNftBumps)]
+pub struct StakeCore<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        seeds = [
+            b"staking",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.staking
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+
+    /// The account representing the staked NFT
+    /// Doesn't use staking.key as one token can only be staked once
+    #[account(
+        init,
+        payer = staker,
+        space = StakedNft::LEN,
+        seeds = [
+            b"staked_nft",
+            asset.key().as_ref()
+        ],
+        bump
+    )]
+    pub staked_nft: Box<Account<'info, StakedNft>>,
+
+    /// The owner of the NFT being staked
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    /// The Core asset being staked
+    #[account(mut, owner = mpl_core::ID)]
+    /// CHECK: checked in cpi
+    pub asset: UncheckedAccount<'info>,
+
+    /// The collection of the asset, or the Core program if it has none
+    /// CHECK: checked in cpi
+    pub collection: UncheckedAccount<'info>,
+
+    /// The fee receiving account
+    #[account(mut, address = fees_wallet::ID)]
+    /// CHECK: TBD
+    pub fee_receiver_account: AccountInfo<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: checked by address
+    #[account(address = mpl_core::ID)]
+    pub core_program: UncheckedAccount<'info>,
+}
+
+pub fn handler(
+    ctx: Context<StakeCore>,
+    bumps: StakedNftBumps,
+    proof: Vec<[u8; 32]>,
+    rarity_multiplier: u64,
+    lock_tier: Option<u8>,
+    root_index: Option<u8>,
+) -> Result<()> {
+    let staking = &mut ctx.accounts.staking;
+
+    // Check that staking started
+    if staking.start > ctx.accounts.clock.unix_timestamp {
+        return err!(StakingError::TooEarly);
+    }
+
+    // Check that staking did not end
+    if staking.has_ended(ctx.accounts.clock.unix_timestamp) {
+        return err!(StakingError::StakingEnded);
+    }
+
+    // Check the NFT belongs to the project, through the allowlist or the collection
+    let eligible = eligibility::verify_core_asset(
+        staking,
+        &ctx.accounts.asset.to_account_info(),
+        &mut ctx.remaining_accounts.iter(),
+        proof,
+        root_index,
+        rarity_multiplier,
+    )?;
+
+    // Charge fees if the project is not fees exempt
+    if !staking.fees_exempt {
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.staker.to_account_info(),
+                to: ctx.accounts.fee_receiver_account.clone(),
+            });
+        system_program::transfer(cpi_context, fees_wallet::FEES_LAMPORTS)?;
+    }
+
+    // Update staking data
+    staking.nfts_staked += 1;
+
+    // Update staked_nft data
+    let staked_nft = &mut ctx.accounts.staked_nft;
+    staked_nft.bumps = bumps;
+    staked_nft.key = staking.key;
+    staked_nft.mint = ctx.accounts.asset.key();
+    staked_nft.staker = ctx.accounts.staker.key();
+    staked_nft.staked_at = ctx.accounts.clock.unix_timestamp;
+    staked_nft.last_claim = ctx.accounts.clock.unix_timestamp;
+    staked_nft.rarity_multiplier = eligible.rarity_multiplier;
+    staked_nft.group = staking.group_of(&eligible);
+    rewards::lock_stake(staking, staked_nft, lock_tier)?;
+    rewards::register_stake(staking, staked_nft, ctx.accounts.clock.unix_timestamp)?;
+
+    // Freeze the asset in the staker wallet, with the staking as freeze delegate
+    let freeze = mpl_core::add_freeze_delegate(
+        ctx.accounts.asset.key(),
+        ctx.accounts.collection.key(),
+        ctx.accounts.staker.key(),
+        ctx.accounts.staker.key(),
+        staking.key(),
+    );
+
+    invoke(&freeze,
+           &[
+               ctx.accounts.asset.to_account_info(),
+               ctx.accounts.collection.to_account_info(),
+               ctx.accounts.staker.to_account_info(),
+               ctx.accounts.staker.to_account_info(),
+               ctx.accounts.system_program.to_account_info(),
+               ctx.accounts.core_program.to_account_info(),
+           ])?;
+
+    msg!("Token staked");
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Approve, Token, TokenAccount};
+use anchor_lang::system_program;
+
+use mpl_token_metadata::instruction::freeze_delegated_account;
+use solana_program::program::invoke_signed;
+
+use crate::{Staking, StakedNft, StakedNftBumps};
+use crate::eligibility;
+use crate::rewards;
+use crate::errors::StakingError;
+use crate::fees_wallet;
+
+#[derive(Accounts)]
+#[instruction(bumps: StakedNftBumps)]
+pub struct StakeFreeze<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        seeds = [
+            b"staking",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.staking
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+
+    /// The account representing the staked NFT
+    /// Doesn't use staking.key as one token can only be staked once
+    #[account(
+        init,
+        payer = staker,
+        space = StakedNft::LEN,
+        seeds = [
+            b"staked_nft",
+            mint.key().as_ref()
+        ],
+        bump
+    )]
+    pub staked_nft: Box<Account<'info, StakedNft>>,
+
+    /// The owner of the NFT being staked
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    /// The mint of the NFT being staked
+    #[account(mut)]
+    /// CHECK: TBD
+    pub mint: AccountInfo<'info>,
+
+    /// The user account that holds the NFT, frozen while staked
+    #[account(
+        mut, 
+        has_one = mint,
+        constraint = staker_account.owner == staker.key()
+    )]
+    pub staker_account: Box<Account<'info, TokenAccount>>,
+
+    /// The fee receiving account
+    #[account(mut, address = fees_wallet::ID)]
+    /// CHECK: TBD
+    pub fee_receiver_account: AccountInfo<'info>,
+
+    /// The program for interacting with the token
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: checked in cpi
+    pub master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: checked in cpi
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+pub fn handler(
+    ctx: Context<StakeFreeze>,
+    bumps: StakedNftBumps,
+    proof: Vec<[u8; 32]>,
+    rarity_multiplier: u64,
+    lock_tier: Option<u8>,
+    root_index: Option<u8>,
+) -> Result<()> {
+    let staking = &mut ctx.accounts.staking;
+
+    // Check that staking started
+    if staking.start > ctx.accounts.clock.unix_timestamp {
+        return err!(StakingError::TooEarly);
+    }
+
+    // Check that staking did not end
+    if staking.has_ended(ctx.accounts.clock.unix_timestamp) {
+        return err!(StakingError::StakingEnded);
+    }
+
+    // Check the NFT belongs to the project, through the allowlist or the collection
+    let eligible = eligibility::verify_nft(
+        staking,
+        &ctx.accounts.mint.key(),
+        None,
+        None,
+        &mut ctx.remaining_accounts.iter(),
+        proof,
+        root_index,
+        rarity_multiplier,
+    )?;
+
+    // Charge fees if the project is not fees exempt
+    if !staking.fees_exempt {
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.staker.to_account_info(),
+                to: ctx.accounts.fee_receiver_account.clone(),
+            });
+        system_program::transfer(cpi_context, fees_wallet::FEES_LAMPORTS)?;
+    }
+
+    // Update staking data
+    staking.nfts_staked += 1;
+
+    // Update staked_nft data
+    let staked_nft = &mut ctx.accounts.staked_nft;
+    staked_nft.bumps = bumps;
+    staked_nft.key = staking.key;
+    staked_nft.mint = ctx.accounts.mint.key();
+    staked_nft.staker = ctx.accounts.staker.key();
+    staked_nft.staked_at = ctx.accounts.clock.unix_timestamp;
+    staked_nft.last_claim = ctx.accounts.clock.unix_timestamp;
+    staked_nft.rarity_multiplier = eligible.rarity_multiplier;
+    staked_nft.group = staking.group_of(&eligible);
+    rewards::lock_stake(staking, staked_nft, lock_tier)?;
+    rewards::register_stake(staking, staked_nft, ctx.accounts.clock.unix_timestamp)?;
+
+    // Delegate the token to the staking and freeze it in the staker wallet
+    let cpi_context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Approve {
+            to: ctx.accounts.staker_account.to_account_info(),
+            delegate: staking.to_account_info(),
+            authority: ctx.accounts.staker.to_account_info(),
+        });
+    token::approve(cpi_context, 1)?;
+
+    let seeds = &[
+        b"staking".as_ref(),
+        staking.key.as_ref(),
+        &[staking.bumps.staking],
+    ];
+    let signer = &[&seeds[..]];
+
+    let freeze = freeze_delegated_account(
+        mpl_token_metadata::ID,
+        staking.key(),
+        ctx.accounts.staker_account.key(),
+        ctx.accounts.master_edition.key(),
+        ctx.accounts.mint.key(),
+    );
+
+    invoke_signed(&freeze,
+           &[
+               staking.to_account_info(),
+               ctx.accounts.staker_account.to_account_info(),
+               ctx.accounts.master_edition.to_account_info(),
+               ctx.accounts.mint.to_account_info(),
+               ctx.accounts.token_program.to_account_info(),
+           ], signer)?;
+
+    msg!("Token staked");
+
+    Ok(())
+}
@@ -4,7 +4,8 @@ use anchor_lang::system_program;
 use anchor_spl::token::{TokenAccount};
 
 use crate::{Staking, StakedNft, StakedNftBumps};
-use crate::merkle_proof;
+use crate::eligibility;
+use crate::rewards;
 use crate::errors::StakingError;
 use crate::fees_wallet;
 
@@ -39,7 +40,7 @@ pub struct StakeOcp<'info> {
     #[account(
         init,
         payer = staker,
-        space = 8 + 2 + 32 + 32 + 32 + 8 + 8 + 8,
+        space = StakedNft::LEN,
         seeds = [
             b"staked_nft",
             mint.key().as_ref()
@@ -121,7 +122,9 @@ pub fn handler(
     ctx: Context<StakeOcp>,
     bumps: StakedNftBumps,
     proof: Vec<[u8; 32]>,
-    rarity_multiplier: u64
+    rarity_multiplier: u64,
+    lock_tier: Option<u8>,
+    root_index: Option<u8>,
 ) -> Result<()> {
     let staking = &mut ctx.accounts.staking;
 
@@ -130,16 +133,23 @@ pub fn handler(
         return err!(StakingError::TooEarly);
     }
 
-    // Verify the merkle leaf
-    let node = solana_program::keccak::hashv(&[
-        &[0x00],
-        &ctx.accounts.mint.key().to_bytes(),
-        &rarity_multiplier.to_le_bytes(),
-    ]);
-    if !merkle_proof::verify(proof, staking.root, node.0) {
-        return err!(StakingError::InvalidProof);
+    // Check that staking did not end
+    if staking.has_ended(ctx.accounts.clock.unix_timestamp) {
+        return err!(StakingError::StakingEnded);
     }
 
+    // Check the NFT belongs to the project, through the allowlist or the collection
+    let eligible = eligibility::verify_nft(
+        staking,
+        &ctx.accounts.mint.key(),
+        Some(&ctx.accounts.metadata.to_account_info()),
+        Some(&ctx.accounts.instructions.to_account_info()),
+        &mut ctx.remaining_accounts.iter(),
+        proof,
+        root_index,
+        rarity_multiplier,
+    )?;
+
     // Charge fees if the project is not fees exempt
     if !staking.fees_exempt {
         let cpi_context = CpiContext::new(
@@ -162,7 +172,10 @@ pub fn handler(
     staked_nft.staker = ctx.accounts.staker.key();
     staked_nft.staked_at = ctx.accounts.clock.unix_timestamp;
     staked_nft.last_claim = ctx.accounts.clock.unix_timestamp;
-    staked_nft.rarity_multiplier = rarity_multiplier;
+    staked_nft.rarity_multiplier = eligible.rarity_multiplier;
+    staked_nft.group = staking.group_of(&eligible);
+    rewards::lock_stake(staking, staked_nft, lock_tier)?;
+    rewards::register_stake(staking, staked_nft, ctx.accounts.clock.unix_timestamp)?;
 
     // Lock OCP (actual staking)
     open_creator_protocol::cpi::lock(ctx.accounts.lock_context())?;
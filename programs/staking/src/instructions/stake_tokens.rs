@@ -0,0 +1,179 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::{Staking, StakedPosition};
+use crate::rewards;
+use crate::token_interface;
+use crate::errors::StakingError;
+use crate::fees_wallet;
+
+#[derive(Accounts)]
+pub struct StakeTokens<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        seeds = [
+            b"staking",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.staking
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+
+    /// The account holding staking tokens, staking rewards and community funds
+    #[account(
+        seeds = [
+            b"escrow",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.escrow
+    )]
+    /// CHECK: TBD
+    pub escrow: AccountInfo<'info>,
+
+    /// The position of the staker for this token, created on first use
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = StakedPosition::LEN,
+        seeds = [
+            b"position",
+            staking.key.as_ref(),
+            staker.key().as_ref(),
+            mint.key().as_ref()
+        ],
+        bump
+    )]
+    pub staked_position: Box<Account<'info, StakedPosition>>,
+
+    /// The owner of the tokens being staked
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    /// The mint of the tokens being staked
+    #[account(owner = token_program.key())]
+    /// CHECK: TBD
+    pub mint: AccountInfo<'info>,
+
+    /// The user account that holds the tokens
+    #[account(
+        mut,
+        constraint = token_interface::is_account_of(&staker_account, &mint.key(), &staker.key())
+    )]
+    /// CHECK: checked by constraint
+    pub staker_account: UncheckedAccount<'info>,
+
+    /// The project vault holding the staked tokens of this mint, created on first use
+    #[account(
+        mut,
+        seeds = [
+            b"vault",
+            staking.key.as_ref(),
+            mint.key().as_ref()
+        ],
+        bump
+    )]
+    /// CHECK: created in handler
+    pub vault: UncheckedAccount<'info>,
+
+    /// The fee receiving account
+    #[account(mut, address = fees_wallet::ID)]
+    /// CHECK: TBD
+    pub fee_receiver_account: AccountInfo<'info>,
+
+    /// The program for interacting with the token, legacy or Token-2022
+    #[account(constraint = token_interface::is_token_program(token_program.key) @ StakingError::InvalidAccount)]
+    /// CHECK: checked by constraint
+    pub token_program: UncheckedAccount<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<StakeTokens>, amount: u64) -> Result<()> {
+    let staking = &mut ctx.accounts.staking;
+    let now = ctx.accounts.clock.unix_timestamp;
+
+    // Check that staking started
+    if staking.start > now {
+        return err!(StakingError::TooEarly);
+    }
+
+    // Check that staking did not end
+    if staking.has_ended(now) {
+        return err!(StakingError::StakingEnded);
+    }
+
+    if amount == 0 {
+        return err!(StakingError::InvalidAmount);
+    }
+    if !staking.token_weights.iter().any(|token| token.mint == ctx.accounts.mint.key()) {
+        return err!(StakingError::TokenNotStakeable);
+    }
+
+    // Charge fees if the project is not fees exempt
+    if !staking.fees_exempt {
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.staker.to_account_info(),
+                to: ctx.accounts.fee_receiver_account.clone(),
+            });
+        system_program::transfer(cpi_context, fees_wallet::FEES_LAMPORTS)?;
+    }
+
+    // Create the vault with the first deposit of the mint, lamports sent to it beforehand are kept
+    if ctx.accounts.vault.data_is_empty() {
+        let vault_seeds = &[
+            b"vault".as_ref(),
+            staking.key.as_ref(),
+            ctx.accounts.mint.key.as_ref(),
+            &[*ctx.bumps.get("vault").unwrap()],
+        ];
+        token_interface::create_account(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.mint,
+            &ctx.accounts.escrow,
+            &ctx.accounts.staker,
+            &ctx.accounts.system_program,
+            &[&vault_seeds[..]],
+        )?;
+    }
+
+    // Position of the staker, created on first use
+    let staked_position = &mut ctx.accounts.staked_position;
+    if staked_position.staker == Pubkey::default() {
+        staked_position.bump = *ctx.bumps.get("staked_position").unwrap();
+        staked_position.key = staking.key;
+        staked_position.staker = ctx.accounts.staker.key();
+        staked_position.mint = ctx.accounts.mint.key();
+        staked_position.decimals = token_interface::decimals(&ctx.accounts.mint)?;
+        staked_position.staked_at = now;
+        staked_position.last_claim = now;
+    }
+
+    // Rewards earned so far are kept at the previous amount
+    rewards::settle_position(staking, staked_position, now)?;
+
+    // Only what reaches the vault is staked, net of any transfer fee
+    let received = token_interface::transfer_checked(
+        &ctx.accounts.token_program,
+        &ctx.accounts.staker_account,
+        &ctx.accounts.mint,
+        &ctx.accounts.vault,
+        &ctx.accounts.staker,
+        amount,
+        &[],
+    )?;
+    staked_position.amount = staked_position.amount
+        .checked_add(received)
+        .ok_or_else(|| error!(StakingError::MathOverflow))?;
+    rewards::sync_position_weight(staking, staked_position)?;
+
+    msg!("Tokens staked: {}", received);
+
+    Ok(())
+}
@@ -0,0 +1,244 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use solana_program::program::invoke_signed;
+
+use crate::fees_wallet;
+use crate::bubblegum;
+use crate::{StakedNft, Staking, OwedRewards, CompressedLeaf};
+use crate::rewards;
+use crate::token_interface;
+use crate::errors::StakingError;
+
+#[derive(Accounts)]
+#[instruction(leaf: CompressedLeaf)]
+pub struct UnstakeCnft<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        seeds = [
+            b"staking",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.staking
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+
+    /// The account holding staking tokens, staking rewards and community funds
+    /// Custodies the staked compressed NFTs
+    #[account(
+        seeds = [
+            b"escrow",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.escrow
+    )]
+    /// CHECK: TBD
+    pub escrow: AccountInfo<'info>,
+
+    /// The account that will hold the rewards token
+    #[account(
+        mut,
+        seeds = [
+            b"rewards",
+            staking.key.as_ref(),
+            staking.mint.as_ref()
+        ],
+        bump = staking.bumps.rewards,
+    )]
+    /// CHECK: token account of the reward mint, read through the token interface
+    pub rewards_account: UncheckedAccount<'info>,
+
+    /// The account representing the staked NFT
+    #[account(
+        mut,
+        close = staker,
+        has_one = staker,
+        constraint = staked_nft.key == staking.key @ StakingError::InvalidAccount,
+        constraint = staked_nft.mint == bubblegum::asset_id(&merkle_tree.key(), leaf.nonce)
+    )]
+    pub staked_nft: Box<Account<'info, StakedNft>>,
+
+    /// The owner of the staked NFT
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    /// The mint of the reward token
+    #[account(mut)]
+    /// CHECK: TBD
+    pub rewards_mint: AccountInfo<'info>,
+
+    /// The user account receiving rewards
+    #[account(
+        mut,
+        constraint = token_interface::is_account_of(&staker_rewards_account, &rewards_mint.key(), &staker.key())
+    )]
+    /// CHECK: checked by constraint
+    pub staker_rewards_account: UncheckedAccount<'info>,
+
+    /// The rewards owed to the staker when the vault could not pay in full
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = OwedRewards::LEN,
+        seeds = [
+            b"owed",
+            staking.key.as_ref(),
+            staker.key().as_ref()
+        ],
+        bump
+    )]
+    pub owed_rewards: Box<Account<'info, OwedRewards>>,
+
+    /// CHECK: checked in cpi
+    pub tree_authority: UncheckedAccount<'info>,
+
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// The fee receiving account
+    #[account(mut, address = fees_wallet::ID)]
+    /// CHECK: TBD
+    pub fee_receiver_account: AccountInfo<'info>,
+
+    /// The program for interacting with the reward token, legacy or Token-2022
+    #[account(constraint = token_interface::is_token_program(rewards_token_program.key) @ StakingError::InvalidAccount)]
+    /// CHECK: checked by constraint
+    pub rewards_token_program: UncheckedAccount<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+
+    /// CHECK: checked by address
+    #[account(address = bubblegum::spl_noop::ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: checked by address
+    #[account(address = bubblegum::spl_account_compression::ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: checked by address
+    #[account(address = bubblegum::ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Unstake the staked compressed NFT.
+/// The treasury, when the project has one, comes first in the remaining accounts,
+/// followed by the nodes of the leaf proof not covered by the tree canopy
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, UnstakeCnft<'info>>,
+    leaf: CompressedLeaf,
+) -> Result<()> {
+    let staking = &mut ctx.accounts.staking;
+    let staked_nft = &mut ctx.accounts.staked_nft;
+
+    let has_treasury = staking.early_unstake_penalty.treasury != Pubkey::default();
+    let (treasury_accounts, proof) = ctx.remaining_accounts
+        .split_at(if has_treasury { 1.min(ctx.remaining_accounts.len()) } else { 0 });
+
+    // Check that the NFT is not locked
+    if staked_nft.is_locked(staking, ctx.accounts.clock.unix_timestamp) {
+        return err!(StakingError::StakeLocked);
+    }
+
+    // Charge fees if the project is not fees exempt
+    if !staking.fees_exempt {
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.staker.to_account_info(),
+                to: ctx.accounts.fee_receiver_account.clone(),
+            });
+        system_program::transfer(cpi_context, fees_wallet::FEES_LAMPORTS)?;
+    }
+
+    // Update staking data
+    staking.nfts_staked -= 1;
+    let rewards_amount = rewards::settle_rewards(
+        staking,
+        staked_nft,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+    rewards::unregister_stake(staking, staked_nft);
+    let forfeited = staking.early_unstake_forfeit(
+        staked_nft,
+        rewards_amount,
+        ctx.accounts.clock.unix_timestamp,
+    );
+
+    // Rewards owed to the staker, created on first use
+    let amount_paid = rewards::pay_or_owe_staker(
+        staking,
+        &mut ctx.accounts.owed_rewards,
+        *ctx.bumps.get("owed_rewards").unwrap(),
+        ctx.accounts.staker.key(),
+        rewards_amount - forfeited,
+        token_interface::amount(&ctx.accounts.rewards_account)?,
+    )?;
+
+    let seeds = &[
+        b"escrow".as_ref(),
+        staking.key.as_ref(),
+        &[staking.bumps.escrow],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Return the compressed NFT to the staker
+    let transfer = bubblegum::transfer(
+        ctx.accounts.tree_authority.key(),
+        ctx.accounts.escrow.key(),
+        ctx.accounts.escrow.key(),
+        ctx.accounts.staker.key(),
+        ctx.accounts.merkle_tree.key(),
+        proof,
+        &leaf,
+    );
+
+    let mut accounts = vec![
+        ctx.accounts.tree_authority.to_account_info(),
+        ctx.accounts.escrow.to_account_info(),
+        ctx.accounts.escrow.to_account_info(),
+        ctx.accounts.staker.to_account_info(),
+        ctx.accounts.merkle_tree.to_account_info(),
+        ctx.accounts.log_wrapper.to_account_info(),
+        ctx.accounts.compression_program.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.bubblegum_program.to_account_info(),
+    ];
+    accounts.extend(proof.iter().cloned());
+
+    invoke_signed(&transfer, &accounts, signer)?;
+
+    // Claim rewards
+    if amount_paid > 0 {
+        let received = token_interface::transfer_checked(
+            &ctx.accounts.rewards_token_program,
+            &ctx.accounts.rewards_account,
+            &ctx.accounts.rewards_mint,
+            &ctx.accounts.staker_rewards_account,
+            &ctx.accounts.escrow,
+            amount_paid,
+            signer,
+        )?;
+        msg!("Rewards paid: {}", received);
+    }
+
+    // Route forfeited rewards to the treasury
+    rewards::route_forfeited(
+        staking,
+        forfeited,
+        treasury_accounts,
+        &ctx.accounts.rewards_token_program,
+        &ctx.accounts.rewards_account,
+        &ctx.accounts.rewards_mint,
+        &ctx.accounts.escrow,
+        signer,
+    )?;
+
+    msg!("Unstaked token");
+
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Revoke, Token, TokenAccount};
+use anchor_lang::system_program;
+
+use mpl_token_metadata::instruction::thaw_delegated_account;
+use solana_program::program::invoke_signed;
+
+use crate::fees_wallet;
+use crate::{StakedNft, Staking, OwedRewards};
+use crate::rewards;
+use crate::token_interface;
+use crate::errors::StakingError;
+
+#[derive(Accounts)]
+pub struct UnstakeFreeze<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        seeds = [
+            b"staking",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.staking
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+
+    /// The account holding staking tokens, staking rewards and community funds
+    #[account(
+        seeds = [
+            b"escrow",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.escrow
+    )]
+    /// CHECK: TBD
+    pub escrow: AccountInfo<'info>,
+
+    /// The account that will hold the rewards token
+    #[account(
+        mut,
+        seeds = [
+            b"rewards",
+            staking.key.as_ref(),
+            staking.mint.as_ref()
+        ],
+        bump = staking.bumps.rewards,
+    )]
+    /// CHECK: token account of the reward mint, read through the token interface
+    pub rewards_account: UncheckedAccount<'info>,
+
+    /// The account representing the staked NFT
+    #[account(
+        mut,
+        close = staker,
+        has_one = mint,
+        has_one = staker,
+        constraint = staked_nft.key == staking.key @ StakingError::InvalidAccount
+    )]
+    pub staked_nft: Box<Account<'info, StakedNft>>,
+
+    /// The owner of the staked NFT
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    /// The mint of the staked NFT
+    #[account(mut)]
+    /// CHECK: TBD
+    pub mint: AccountInfo<'info>,
+
+    /// The user account holding the frozen NFT
+    #[account(
+        mut,
+        has_one = mint,
+        constraint = staker_account.owner == staker.key()
+    )]
+    pub staker_account: Box<Account<'info, TokenAccount>>,
+
+    /// The mint of the reward token
+    #[account(mut)]
+    /// CHECK: TBD
+    pub rewards_mint: AccountInfo<'info>,
+
+    /// The user account receiving rewards
+    #[account(
+        mut,
+        constraint = token_interface::is_account_of(&staker_rewards_account, &rewards_mint.key(), &staker.key())
+    )]
+    /// CHECK: checked by constraint
+    pub staker_rewards_account: UncheckedAccount<'info>,
+
+    /// The rewards owed to the staker when the vault could not pay in full
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = OwedRewards::LEN,
+        seeds = [
+            b"owed",
+            staking.key.as_ref(),
+            staker.key().as_ref()
+        ],
+        bump
+    )]
+    pub owed_rewards: Box<Account<'info, OwedRewards>>,
+
+    /// The fee receiving account
+    #[account(mut, address = fees_wallet::ID)]
+    /// CHECK: TBD
+    pub fee_receiver_account: AccountInfo<'info>,
+
+    /// The program for interacting with the token
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+
+    /// The program for interacting with the reward token, legacy or Token-2022
+    #[account(constraint = token_interface::is_token_program(rewards_token_program.key) @ StakingError::InvalidAccount)]
+    /// CHECK: checked by constraint
+    pub rewards_token_program: UncheckedAccount<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: checked in cpi
+    pub master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: checked in cpi
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+/// Unstake the staked_nft
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeFreeze<'info>>) -> Result<()> {
+    let staking = &mut ctx.accounts.staking;
+    let staked_nft = &mut ctx.accounts.staked_nft;
+
+    // Check that the NFT is not locked
+    if staked_nft.is_locked(staking, ctx.accounts.clock.unix_timestamp) {
+        return err!(StakingError::StakeLocked);
+    }
+
+    // Charge fees if the project is not fees exempt
+    if !staking.fees_exempt {
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.staker.to_account_info(),
+                to: ctx.accounts.fee_receiver_account.clone(),
+            });
+        system_program::transfer(cpi_context, fees_wallet::FEES_LAMPORTS)?;
+    }
+
+    // Update staking data
+    staking.nfts_staked -= 1;
+    let rewards_amount = rewards::settle_rewards(
+        staking,
+        staked_nft,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+    rewards::unregister_stake(staking, staked_nft);
+    let forfeited = staking.early_unstake_forfeit(
+        staked_nft,
+        rewards_amount,
+        ctx.accounts.clock.unix_timestamp,
+    );
+
+    // Rewards owed to the staker, created on first use
+    let amount_paid = rewards::pay_or_owe_staker(
+        staking,
+        &mut ctx.accounts.owed_rewards,
+        *ctx.bumps.get("owed_rewards").unwrap(),
+        ctx.accounts.staker.key(),
+        rewards_amount - forfeited,
+        token_interface::amount(&ctx.accounts.rewards_account)?,
+    )?;
+
+    let seeds = &[
+        b"escrow".as_ref(),
+        staking.key.as_ref(),
+        &[staking.bumps.escrow],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Thaw the NFT in the staker wallet and revoke the staking delegate
+    let staking_seeds = &[
+        b"staking".as_ref(),
+        staking.key.as_ref(),
+        &[staking.bumps.staking],
+    ];
+    let staking_signer = &[&staking_seeds[..]];
+
+    let thaw = thaw_delegated_account(
+        mpl_token_metadata::ID,
+        staking.key(),
+        ctx.accounts.staker_account.key(),
+        ctx.accounts.master_edition.key(),
+        ctx.accounts.mint.key(),
+    );
+
+    invoke_signed(&thaw,
+           &[
+               staking.to_account_info(),
+               ctx.accounts.staker_account.to_account_info(),
+               ctx.accounts.master_edition.to_account_info(),
+               ctx.accounts.mint.to_account_info(),
+               ctx.accounts.token_program.to_account_info(),
+           ], staking_signer)?;
+
+    let revoke_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Revoke {
+            source: ctx.accounts.staker_account.to_account_info(),
+            authority: ctx.accounts.staker.to_account_info(),
+        },
+    );
+    token::revoke(revoke_ctx)?;
+
+    // Claim rewards
+    if amount_paid > 0 {
+        let received = token_interface::transfer_checked(
+            &ctx.accounts.rewards_token_program,
+            &ctx.accounts.rewards_account,
+            &ctx.accounts.rewards_mint,
+            &ctx.accounts.staker_rewards_account,
+            &ctx.accounts.escrow,
+            amount_paid,
+            signer,
+        )?;
+        msg!("Rewards paid: {}", received);
+    }
+
+    // Route forfeited rewards to the treasury
+    rewards::route_forfeited(
+        staking,
+        forfeited,
+        ctx.remaining_accounts,
+        &ctx.accounts.rewards_token_program,
+        &ctx.accounts.rewards_account,
+        &ctx.accounts.rewards_mint,
+        &ctx.accounts.escrow,
+        signer,
+    )?;
+
+    msg!("Unstaked token");
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::sysvar;
 use anchor_lang::system_program;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, Token, TokenAccount};
 
 use mpl_token_metadata::{
     state::{TokenStandard, Metadata, TokenMetadataAccount, TokenRecord, TokenDelegateRole},
@@ -9,7 +9,10 @@ use mpl_token_metadata::{
         RevokeArgs, UnlockArgs, InstructionBuilder, builders::{RevokeBuilder, UnlockBuilder}}};
 use solana_program::program::{invoke, invoke_signed};
 
-use crate::{Staking, StakedNft};
+use crate::{Staking, StakedNft, OwedRewards};
+use crate::rewards;
+use crate::token_interface;
+use crate::errors::StakingError;
 use crate::fees_wallet;
 
 #[derive(Accounts)]
@@ -46,14 +49,16 @@ pub struct UnstakeMpl<'info> {
         ],
         bump = staking.bumps.rewards,
     )]
-    pub rewards_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: token account of the reward mint, read through the token interface
+    pub rewards_account: UncheckedAccount<'info>,
 
     /// The account representing the staked NFT
     #[account(
         mut,
         close = staker,
         has_one = mint,
-        has_one = staker
+        has_one = staker,
+        constraint = staked_nft.key == staking.key @ StakingError::InvalidAccount
     )]
     pub staked_nft: Box<Account<'info, StakedNft>>,
 
@@ -82,11 +87,24 @@ pub struct UnstakeMpl<'info> {
     /// The user account receiving rewards
     #[account(
         mut,
-        constraint =
-            staker_rewards_account.owner == staker.key() &&
-            staker_rewards_account.mint == rewards_mint.key()
+        constraint = token_interface::is_account_of(&staker_rewards_account, &rewards_mint.key(), &staker.key())
     )]
-    pub staker_rewards_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: checked by constraint
+    pub staker_rewards_account: UncheckedAccount<'info>,
+
+    /// The rewards owed to the staker when the vault could not pay in full
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = OwedRewards::LEN,
+        seeds = [
+            b"owed",
+            staking.key.as_ref(),
+            staker.key().as_ref()
+        ],
+        bump
+    )]
+    pub owed_rewards: Box<Account<'info, OwedRewards>>,
 
     /// The fee receiving account
     #[account(mut, address = fees_wallet::ID)]
@@ -114,6 +132,11 @@ pub struct UnstakeMpl<'info> {
     #[account(address = token::ID)]
     pub token_program: Program<'info, Token>,
 
+    /// The program for interacting with the reward token, legacy or Token-2022
+    #[account(constraint = token_interface::is_token_program(rewards_token_program.key) @ StakingError::InvalidAccount)]
+    /// CHECK: checked by constraint
+    pub rewards_token_program: UncheckedAccount<'info>,
+
     /// CHECK: checked in cpi
     pub authorization_rules_program: UncheckedAccount<'info>,
 
@@ -129,10 +152,15 @@ pub struct UnstakeMpl<'info> {
 }
 
 /// Unstake the staked_nft
-pub fn handler(ctx: Context<UnstakeMpl>) -> Result<()> {
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeMpl<'info>>) -> Result<()> {
     let staking = &mut ctx.accounts.staking;
     let staked_nft = &mut ctx.accounts.staked_nft;
 
+    // Check that the NFT is not locked
+    if staked_nft.is_locked(staking, ctx.accounts.clock.unix_timestamp) {
+        return err!(StakingError::StakeLocked);
+    }
+
     // Charge fees if the project is not fees exempt
     if !staking.fees_exempt {
         let cpi_context = CpiContext::new(
@@ -145,7 +173,28 @@ pub fn handler(ctx: Context<UnstakeMpl>) -> Result<()> {
     }
 
     // Update staking data
-    staking.nfts_staked -= 1;    
+    staking.nfts_staked -= 1;
+    let rewards_amount = rewards::settle_rewards(
+        staking,
+        staked_nft,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+    rewards::unregister_stake(staking, staked_nft);
+    let forfeited = staking.early_unstake_forfeit(
+        staked_nft,
+        rewards_amount,
+        ctx.accounts.clock.unix_timestamp,
+    );
+
+    // Rewards owed to the staker, created on first use
+    let amount_paid = rewards::pay_or_owe_staker(
+        staking,
+        &mut ctx.accounts.owed_rewards,
+        *ctx.bumps.get("owed_rewards").unwrap(),
+        ctx.accounts.staker.key(),
+        rewards_amount - forfeited,
+        token_interface::amount(&ctx.accounts.rewards_account)?,
+    )?;
 
     // Unlock pNFT (actual unstaking)
     let seeds = &[
@@ -245,13 +294,6 @@ pub fn handler(ctx: Context<UnstakeMpl>) -> Result<()> {
            ])?;
 
     // Claim rewards
-    let rarity_multiplier = staked_nft.rarity_multiplier;
-    let seconds_elapsed = ctx.accounts.clock.unix_timestamp - staked_nft.last_claim;
-    let daily_rewards_adjusted = staking.daily_rewards * rarity_multiplier / 100;
-    let rewards_amount = daily_rewards_adjusted * (seconds_elapsed as u64) / 86400;
-
-    staked_nft.last_claim = ctx.accounts.clock.unix_timestamp;
-
     let seeds_claim = &[
         b"escrow".as_ref(),
         staking.key.as_ref(),
@@ -259,22 +301,31 @@ pub fn handler(ctx: Context<UnstakeMpl>) -> Result<()> {
     ];
     let signer_claim = &[&seeds_claim[..]];
 
-    let context = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.rewards_account.to_account_info(),
-            to: ctx.accounts.staker_rewards_account.to_account_info(),
-            authority: ctx.accounts.escrow.to_account_info(),
-        },
-        signer_claim,
-    );
-
-    if ctx.accounts.rewards_account.amount < rewards_amount {
-        msg!("Rewards not claimed, not enough funds");
-    } else {
-        token::transfer(context, rewards_amount)?;
+    if amount_paid > 0 {
+        let received = token_interface::transfer_checked(
+            &ctx.accounts.rewards_token_program,
+            &ctx.accounts.rewards_account,
+            &ctx.accounts.rewards_mint,
+            &ctx.accounts.staker_rewards_account,
+            &ctx.accounts.escrow,
+            amount_paid,
+            signer_claim,
+        )?;
+        msg!("Rewards paid: {}", received);
     }
 
+    // Route forfeited rewards to the treasury
+    rewards::route_forfeited(
+        staking,
+        forfeited,
+        ctx.remaining_accounts,
+        &ctx.accounts.rewards_token_program,
+        &ctx.accounts.rewards_account,
+        &ctx.accounts.rewards_mint,
+        &ctx.accounts.escrow,
+        signer_claim,
+    )?;
+
     msg!("Unstaked token");
 
     Ok(())
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::sysvar;
-use anchor_spl::token::{self, CloseAccount, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount};
 use anchor_lang::system_program;
 
 use mpl_token_metadata::{
@@ -10,7 +10,10 @@ use mpl_token_metadata::{
 
 use solana_program::program::{invoke_signed};
 use crate::fees_wallet;
-use crate::{StakedNft, Staking};
+use crate::{StakedNft, Staking, OwedRewards};
+use crate::rewards;
+use crate::token_interface;
+use crate::errors::StakingError;
 
 #[derive(Accounts)]
 pub struct UnstakeMplCustodial<'info> {
@@ -46,14 +49,16 @@ pub struct UnstakeMplCustodial<'info> {
         ],
         bump = staking.bumps.rewards,
     )]
-    pub rewards_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: token account of the reward mint, read through the token interface
+    pub rewards_account: UncheckedAccount<'info>,
 
     /// The account representing the staked NFT
     #[account(
         mut,
         close = staker,
         has_one = mint,
-        has_one = staker
+        has_one = staker,
+        constraint = staked_nft.key == staking.key @ StakingError::InvalidAccount
     )]
     pub staked_nft: Box<Account<'info, StakedNft>>,
 
@@ -82,11 +87,24 @@ pub struct UnstakeMplCustodial<'info> {
     /// The user account receiving rewards
     #[account(
         mut,
-        constraint =
-            staker_rewards_account.owner == staker.key() &&
-            staker_rewards_account.mint == rewards_mint.key()
+        constraint = token_interface::is_account_of(&staker_rewards_account, &rewards_mint.key(), &staker.key())
     )]
-    pub staker_rewards_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: checked by constraint
+    pub staker_rewards_account: UncheckedAccount<'info>,
+
+    /// The rewards owed to the staker when the vault could not pay in full
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = OwedRewards::LEN,
+        seeds = [
+            b"owed",
+            staking.key.as_ref(),
+            staker.key().as_ref()
+        ],
+        bump
+    )]
+    pub owed_rewards: Box<Account<'info, OwedRewards>>,
 
     /// The account that holds the staked NFT
     #[account(
@@ -130,6 +148,11 @@ pub struct UnstakeMplCustodial<'info> {
     #[account(address = token::ID)]
     pub token_program: Program<'info, Token>,
 
+    /// The program for interacting with the reward token, legacy or Token-2022
+    #[account(constraint = token_interface::is_token_program(rewards_token_program.key) @ StakingError::InvalidAccount)]
+    /// CHECK: checked by constraint
+    pub rewards_token_program: UncheckedAccount<'info>,
+
     /// CHECK: checked in cpi
     pub authorization_rules_program: UncheckedAccount<'info>,
 
@@ -148,10 +171,15 @@ pub struct UnstakeMplCustodial<'info> {
 }
 
 /// Unstake the staked_nft
-pub fn handler(ctx: Context<UnstakeMplCustodial>) -> Result<()> {
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeMplCustodial<'info>>) -> Result<()> {
     let staking = &mut ctx.accounts.staking;
     let staked_nft = &mut ctx.accounts.staked_nft;
 
+    // Check that the NFT is not locked
+    if staked_nft.is_locked(staking, ctx.accounts.clock.unix_timestamp) {
+        return err!(StakingError::StakeLocked);
+    }
+
     // Charge fees if the project is not fees exempt
     if !staking.fees_exempt {
         let cpi_context = CpiContext::new(
@@ -165,6 +193,27 @@ pub fn handler(ctx: Context<UnstakeMplCustodial>) -> Result<()> {
 
     // Update staking data
     staking.nfts_staked -= 1;
+    let rewards_amount = rewards::settle_rewards(
+        staking,
+        staked_nft,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+    rewards::unregister_stake(staking, staked_nft);
+    let forfeited = staking.early_unstake_forfeit(
+        staked_nft,
+        rewards_amount,
+        ctx.accounts.clock.unix_timestamp,
+    );
+
+    // Rewards owed to the staker, created on first use
+    let amount_paid = rewards::pay_or_owe_staker(
+        staking,
+        &mut ctx.accounts.owed_rewards,
+        *ctx.bumps.get("owed_rewards").unwrap(),
+        ctx.accounts.staker.key(),
+        rewards_amount - forfeited,
+        token_interface::amount(&ctx.accounts.rewards_account)?,
+    )?;
 
     let seeds = &[
         b"escrow".as_ref(),
@@ -235,28 +284,30 @@ pub fn handler(ctx: Context<UnstakeMplCustodial>) -> Result<()> {
     token::close_account(close_account_ctx)?;
 
     // Claim rewards
-    let rarity_multiplier = staked_nft.rarity_multiplier;
-    let seconds_elapsed = ctx.accounts.clock.unix_timestamp - staked_nft.last_claim;
-    let daily_rewards_adjusted = staking.daily_rewards * rarity_multiplier / 100;
-    let rewards_amount = daily_rewards_adjusted * (seconds_elapsed as u64) / 86400;
-
-    staked_nft.last_claim = ctx.accounts.clock.unix_timestamp;
+    if amount_paid > 0 {
+        let received = token_interface::transfer_checked(
+            &ctx.accounts.rewards_token_program,
+            &ctx.accounts.rewards_account,
+            &ctx.accounts.rewards_mint,
+            &ctx.accounts.staker_rewards_account,
+            &ctx.accounts.escrow,
+            amount_paid,
+            signer,
+        )?;
+        msg!("Rewards paid: {}", received);
+    }
 
-    let context = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.rewards_account.to_account_info(),
-            to: ctx.accounts.staker_rewards_account.to_account_info(),
-            authority: ctx.accounts.escrow.to_account_info(),
-        },
+    // Route forfeited rewards to the treasury
+    rewards::route_forfeited(
+        staking,
+        forfeited,
+        ctx.remaining_accounts,
+        &ctx.accounts.rewards_token_program,
+        &ctx.accounts.rewards_account,
+        &ctx.accounts.rewards_mint,
+        &ctx.accounts.escrow,
         signer,
-    );
-
-    if ctx.accounts.rewards_account.amount < rewards_amount {
-        msg!("Rewards not claimed, not enough funds");
-    } else {
-        token::transfer(context, rewards_amount)?;
-    }
+    )?;
 
     msg!("Unstaked token");
 
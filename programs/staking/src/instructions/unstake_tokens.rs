@@ -0,0 +1,215 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::fees_wallet;
+use crate::{StakedPosition, Staking, OwedRewards};
+use crate::rewards;
+use crate::token_interface;
+use crate::errors::StakingError;
+
+#[derive(Accounts)]
+pub struct UnstakeTokens<'info> {
+    /// The Staking state account
+    #[account(
+        mut,
+        seeds = [
+            b"staking",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.staking
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+
+    /// The account holding staking tokens, staking rewards and community funds
+    #[account(
+        seeds = [
+            b"escrow",
+            staking.key.as_ref()
+        ],
+        bump = staking.bumps.escrow
+    )]
+    /// CHECK: TBD
+    pub escrow: AccountInfo<'info>,
+
+    /// The account that will hold the rewards token
+    #[account(
+        mut,
+        seeds = [
+            b"rewards",
+            staking.key.as_ref(),
+            staking.mint.as_ref()
+        ],
+        bump = staking.bumps.rewards,
+    )]
+    /// CHECK: token account of the reward mint, read through the token interface
+    pub rewards_account: UncheckedAccount<'info>,
+
+    /// The position the tokens are unstaked from
+    #[account(
+        mut,
+        seeds = [
+            b"position",
+            staking.key.as_ref(),
+            staker.key().as_ref(),
+            mint.key().as_ref()
+        ],
+        bump = staked_position.bump,
+        has_one = mint,
+        has_one = staker
+    )]
+    pub staked_position: Box<Account<'info, StakedPosition>>,
+
+    /// The owner of the staked tokens
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    /// The mint of the staked tokens
+    /// CHECK: TBD
+    pub mint: AccountInfo<'info>,
+
+    /// The user account receiving the tokens
+    #[account(
+        mut,
+        constraint = token_interface::is_account_of(&staker_account, &mint.key(), &staker.key())
+    )]
+    /// CHECK: checked by constraint
+    pub staker_account: UncheckedAccount<'info>,
+
+    /// The project vault holding the staked tokens of this mint
+    #[account(
+        mut,
+        seeds = [
+            b"vault",
+            staking.key.as_ref(),
+            mint.key().as_ref()
+        ],
+        bump
+    )]
+    /// CHECK: token account of the staked mint, read through the token interface
+    pub vault: UncheckedAccount<'info>,
+
+    /// The mint of the reward token
+    /// CHECK: TBD
+    pub rewards_mint: AccountInfo<'info>,
+
+    /// The user account receiving rewards
+    #[account(
+        mut,
+        constraint = token_interface::is_account_of(&staker_rewards_account, &rewards_mint.key(), &staker.key())
+    )]
+    /// CHECK: checked by constraint
+    pub staker_rewards_account: UncheckedAccount<'info>,
+
+    /// The rewards owed to the staker when the vault could not pay in full
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = OwedRewards::LEN,
+        seeds = [
+            b"owed",
+            staking.key.as_ref(),
+            staker.key().as_ref()
+        ],
+        bump
+    )]
+    pub owed_rewards: Box<Account<'info, OwedRewards>>,
+
+    /// The fee receiving account
+    #[account(mut, address = fees_wallet::ID)]
+    /// CHECK: TBD
+    pub fee_receiver_account: AccountInfo<'info>,
+
+    /// The program for interacting with the staked token, legacy or Token-2022
+    #[account(constraint = token_interface::is_token_program(token_program.key) @ StakingError::InvalidAccount)]
+    /// CHECK: checked by constraint
+    pub token_program: UncheckedAccount<'info>,
+
+    /// The program for interacting with the reward token, legacy or Token-2022
+    #[account(constraint = token_interface::is_token_program(rewards_token_program.key) @ StakingError::InvalidAccount)]
+    /// CHECK: checked by constraint
+    pub rewards_token_program: UncheckedAccount<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Unstakes `amount` tokens from the position and pays its rewards.
+/// The position is closed once empty
+pub fn handler(ctx: Context<UnstakeTokens>, amount: u64) -> Result<()> {
+    let staking = &mut ctx.accounts.staking;
+    let staked_position = &mut ctx.accounts.staked_position;
+    let now = ctx.accounts.clock.unix_timestamp;
+
+    if amount == 0 || amount > staked_position.amount {
+        return err!(StakingError::InvalidAmount);
+    }
+
+    // Charge fees if the project is not fees exempt
+    if !staking.fees_exempt {
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.staker.to_account_info(),
+                to: ctx.accounts.fee_receiver_account.clone(),
+            });
+        system_program::transfer(cpi_context, fees_wallet::FEES_LAMPORTS)?;
+    }
+
+    // Update staking data
+    rewards::settle_position(staking, staked_position, now)?;
+    staked_position.amount -= amount;
+    rewards::sync_position_weight(staking, staked_position)?;
+    let rewards_amount = rewards::take_position_rewards(staking, staked_position);
+
+    // Rewards owed to the staker, created on first use
+    let amount_paid = rewards::pay_or_owe_staker(
+        staking,
+        &mut ctx.accounts.owed_rewards,
+        *ctx.bumps.get("owed_rewards").unwrap(),
+        ctx.accounts.staker.key(),
+        rewards_amount,
+        token_interface::amount(&ctx.accounts.rewards_account)?,
+    )?;
+
+    let seeds = &[
+        b"escrow".as_ref(),
+        staking.key.as_ref(),
+        &[staking.bumps.escrow],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Return the tokens
+    let received = token_interface::transfer_checked(
+        &ctx.accounts.token_program,
+        &ctx.accounts.vault,
+        &ctx.accounts.mint,
+        &ctx.accounts.staker_account,
+        &ctx.accounts.escrow,
+        amount,
+        signer,
+    )?;
+    msg!("Tokens unstaked: {}", received);
+
+    // Claim rewards
+    if amount_paid > 0 {
+        let received = token_interface::transfer_checked(
+            &ctx.accounts.rewards_token_program,
+            &ctx.accounts.rewards_account,
+            &ctx.accounts.rewards_mint,
+            &ctx.accounts.staker_rewards_account,
+            &ctx.accounts.escrow,
+            amount_paid,
+            signer,
+        )?;
+        msg!("Rewards paid: {}", received);
+    }
+
+    // Rewards not paid are owed, an empty position has nothing left
+    if staked_position.amount == 0 {
+        staked_position.close(ctx.accounts.staker.to_account_info())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::{Staking, StakingUpdate};
+use crate::errors::StakingError;
+use crate::roles;
+
+#[derive(Accounts)]
+pub struct UpdateStaking<'info> {
+    /// The Staking state account
+    #[account(mut)]
+    pub staking: Account<'info, Staking>,
+
+    /// The owner of the staking, or a wallet with the permissions of the fields changed
+    pub owner: Signer<'info>,
+
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Changes the staking parameters given in `update` and logs each change.
+/// The root needs the allowlist permission, the other fields the config permission
+pub fn handler(
+    ctx: Context<UpdateStaking>,
+    update: StakingUpdate,
+) -> Result<()> {
+
+    let staking = &mut ctx.accounts.staking;
+    let now = ctx.accounts.clock.unix_timestamp;
+
+    let signer = ctx.accounts.owner.key;
+    let changes_config = update.daily_rewards.is_some() || update.start.is_some() || update.end.is_some();
+    if (changes_config && !staking.can(signer, roles::CONFIG))
+        || (update.root.is_some() && !staking.can(signer, roles::ALLOWLIST))
+    {
+        return err!(StakingError::Unauthorized);
+    }
+
+    // Root changes go through a proposal once approvals are set
+    if staking.uses_approvals() && update.root.is_some_and(|root| root != staking.root) {
+        return err!(StakingError::NeedsApproval);
+    }
+
+    apply_update(staking, &update, now)
+}
+
+/// Changes the staking parameters given in `update` and logs each change
+pub fn apply_update(staking: &mut Staking, update: &StakingUpdate, now: i64) -> Result<()> {
+    if let Some(daily_rewards) = update.daily_rewards {
+        staking.set_daily_rewards(daily_rewards, now)?;
+        msg!("Daily rewards set: {}", daily_rewards);
+    }
+
+    if let Some(start) = update.start {
+        staking.set_start(start, now)?;
+        msg!("Start set: {}", start);
+    }
+
+    if let Some(root) = update.root {
+        staking.set_root(root, now);
+        msg!("Root set: {:?}", root);
+    }
+
+    if let Some(end) = update.end {
+        staking.set_end(Some(end), now)?;
+        msg!("End set: {}", end);
+    }
+
+    Ok(())
+}
@@ -1,19 +1,22 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
 use crate::Staking;
+use crate::errors::StakingError;
+use crate::token_interface;
+use crate::roles;
 
 #[derive(Accounts)]
 pub struct WithdrawRewards<'info> {
     /// The Staking state account
     #[account(
+        mut,
         seeds = [
             b"staking",
             staking.key.as_ref()
         ],
         bump = staking.bumps.staking,
         has_one = rewards_account,
-        has_one = owner,
+        constraint = staking.can(owner.key, roles::TREASURER) @ StakingError::Unauthorized,
     )]
     pub staking: Account<'info, Staking>,
 
@@ -43,27 +46,87 @@ pub struct WithdrawRewards<'info> {
         ],
         bump = staking.bumps.rewards
     )]
-    pub rewards_account: Account<'info, TokenAccount>,
+    /// CHECK: token account of the reward mint, read through the token interface
+    pub rewards_account: UncheckedAccount<'info>,
 
-    /// The wallet that will own the staking
+    /// The owner of the staking, or a wallet with the treasurer permission
     pub owner: Signer<'info>,
 
     /// The old staking rewards account
     #[account(
         mut,
-        has_one = mint,
-        constraint = owner_account.owner == owner.key()
+        constraint = token_interface::is_account_of(&owner_account, &mint.key(), &owner.key())
     )]
-    pub owner_account: Account<'info, TokenAccount>,
+    /// CHECK: checked by constraint
+    pub owner_account: UncheckedAccount<'info>,
+
+    /// The program for interacting with the reward token, legacy or Token-2022
+    #[account(constraint = token_interface::is_token_program(token_program.key) @ StakingError::InvalidAccount)]
+    /// CHECK: checked by constraint
+    pub token_program: UncheckedAccount<'info>,
 
-    /// The program for interacting with the token.
-    #[account(address = token::ID)]
-    pub token_program: Program<'info, Token>,
+    /// Clock account used to know the time
+    pub clock: Sysvar<'info, Clock>,
 }
 
-/// Lets owner withdraw some rewards from the escrow without changing the token
-pub fn handler(ctx: Context<WithdrawRewards>, amount: u64) -> Result<()> {
-    let staking = &ctx.accounts.staking;
+/// Lets owner withdraw some rewards from the escrow without changing the token.
+/// Past the approval limit of the day, or for a sunset, the withdrawal needs a proposal
+pub fn handler(ctx: Context<WithdrawRewards>, amount: u64, sunset: bool) -> Result<()> {
+    let staking = &mut ctx.accounts.staking;
+    let now = ctx.accounts.clock.unix_timestamp;
+
+    // A sunset ends the staking, which is a config change
+    if sunset && !staking.can(ctx.accounts.owner.key, roles::CONFIG) {
+        return err!(StakingError::Unauthorized);
+    }
+
+    if staking.uses_approvals() && sunset {
+        return err!(StakingError::NeedsApproval);
+    }
+    staking.record_withdrawal(amount, now)?;
+
+    withdraw(
+        staking,
+        &ctx.accounts.escrow,
+        &ctx.accounts.mint,
+        &ctx.accounts.rewards_account,
+        &ctx.accounts.owner_account,
+        &ctx.accounts.token_program,
+        amount,
+        sunset,
+        now,
+    )
+}
+
+/// Transfers `amount` from the rewards vault to `destination`, keeping enough to pay the
+/// rewards accrued by stakers unless the staking is sunset
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw<'info>(
+    staking: &mut Staking,
+    escrow: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    rewards_account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    amount: u64,
+    sunset: bool,
+    now: i64,
+) -> Result<()> {
+    staking.accrue(now)?;
+
+    // Keep enough to pay the rewards accrued by stakers, unless the staking is sunset
+    let amount_left = token_interface::amount(rewards_account)?.saturating_sub(amount);
+    if amount_left < staking.liability() {
+        if !sunset {
+            return err!(StakingError::WithdrawBelowLiability);
+        }
+        msg!("Sunset: withdrawing below the liability of {}", staking.liability());
+    }
+
+    // A sunset stops rewards from accruing
+    if sunset && !staking.has_ended(now) {
+        staking.set_end(Some(now), now)?;
+    }
 
     // Transfer all tokens left to the owner
     let seeds = &[
@@ -72,16 +135,15 @@ pub fn handler(ctx: Context<WithdrawRewards>, amount: u64) -> Result<()> {
         &[staking.bumps.escrow],
     ];
     let signer = &[&seeds[..]];
-    let transfer_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.rewards_account.to_account_info(),
-            to: ctx.accounts.owner_account.to_account_info(),
-            authority: ctx.accounts.escrow.to_account_info(),
-        },
+    token_interface::transfer_checked(
+        token_program,
+        rewards_account,
+        mint,
+        destination,
+        escrow,
+        amount,
         signer,
-    );
-    token::transfer(transfer_ctx, amount)?;
+    )?;
 
     msg!("Rewards withdrawn");
 
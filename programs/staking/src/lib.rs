@@ -5,6 +5,13 @@ use anchor_lang::prelude::*;
 pub mod errors;
 pub mod instructions;
 pub mod merkle_proof;
+pub mod rewards;
+pub mod bubblegum;
+pub mod mpl_core;
+pub mod eligibility;
+pub mod roles;
+pub mod approvals;
+pub mod token_interface;
 pub mod fees_wallet;
 pub mod fl_auth_wallet;
 
@@ -23,6 +30,8 @@ mod staking {
         daily_rewards: u64,
         start: i64,
         root: [u8; 32],
+        reward_mode: RewardMode,
+        end: Option<i64>,
     ) -> Result<()> {
         instructions::init_staking::handler(
             ctx,
@@ -30,6 +39,8 @@ mod staking {
             daily_rewards,
             start,
             root,
+            reward_mode,
+            end,
         )
     }
 
@@ -39,12 +50,286 @@ mod staking {
         daily_rewards: u64,
         start: i64,
         root: [u8; 32],
+        end: Option<i64>,
     ) -> Result<()> {
         instructions::set_staking::handler(
             ctx,
             daily_rewards,
             start,
             root,
+            end,
+        )
+    }
+
+    /// Changes the staking parameters given in `update`, leaving the others untouched.
+    /// Each change is logged on its own
+    pub fn update_staking(
+        ctx: Context<UpdateStaking>,
+        update: StakingUpdate,
+    ) -> Result<()> {
+        instructions::update_staking::handler(
+            ctx,
+            update,
+        )
+    }
+
+    /// Proposes a new owner for the staking, who becomes owner once it accepts.
+    /// The default key withdraws the proposal
+    pub fn propose_owner(
+        ctx: Context<ProposeOwner>,
+        pending_owner: Pubkey,
+    ) -> Result<()> {
+        instructions::propose_owner::handler(
+            ctx,
+            pending_owner,
+        )
+    }
+
+    /// Makes the proposed owner the owner of the staking
+    pub fn accept_owner(ctx: Context<AcceptOwner>) -> Result<()> {
+        instructions::accept_owner::handler(ctx)
+    }
+
+    /// Grants administration permissions to a wallet, as `roles` bits. No permission removes it
+    pub fn set_role(
+        ctx: Context<SetRole>,
+        key: Pubkey,
+        permissions: u8,
+    ) -> Result<()> {
+        instructions::set_role::handler(
+            ctx,
+            key,
+            permissions,
+        )
+    }
+
+    /// Sets the first approvers of the project and the approvals sensitive actions need.
+    /// Once set, allowlist changes, sunsets, withdrawals past `approval_limit` a day and new
+    /// approvers go through proposals
+    pub fn set_approvers(
+        ctx: Context<SetApprovers>,
+        approvers: Vec<Pubkey>,
+        approval_threshold: u8,
+        approval_limit: u64,
+    ) -> Result<()> {
+        instructions::set_approvers::handler(
+            ctx,
+            approvers,
+            approval_threshold,
+            approval_limit,
+        )
+    }
+
+    /// Proposes a sensitive action to the approvers. Only approvers can propose
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        action: ProposedAction,
+    ) -> Result<()> {
+        instructions::create_proposal::handler(
+            ctx,
+            action,
+        )
+    }
+
+    /// Approves a proposal
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        instructions::approve_proposal::handler(ctx)
+    }
+
+    /// Executes an approved staking update or change of the approvers. Anyone can call it
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        instructions::execute_proposal::handler(ctx)
+    }
+
+    /// Executes an approved withdrawal of rewards. Anyone can call it
+    pub fn execute_withdrawal(ctx: Context<ExecuteWithdrawal>) -> Result<()> {
+        instructions::execute_withdrawal::handler(ctx)
+    }
+
+    /// Executes an approved change of the root registry or of rarities. Anyone can call it
+    pub fn execute_allowlist_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteAllowlistProposal<'info>>,
+    ) -> Result<()> {
+        instructions::execute_allowlist_proposal::handler(ctx)
+    }
+
+    /// Sets the campaign windows rewards accrue in
+    pub fn set_staking_windows(
+        ctx: Context<SetStakingWindows>,
+        windows: Vec<StakingWindow>,
+    ) -> Result<()> {
+        instructions::set_staking_windows::handler(
+            ctx,
+            windows,
+        )
+    }
+
+    /// Sets the lock tiers stakers can commit their NFTs to
+    pub fn set_lock_tiers(
+        ctx: Context<SetLockTiers>,
+        lock_tiers: Vec<LockTier>,
+    ) -> Result<()> {
+        instructions::set_lock_tiers::handler(
+            ctx,
+            lock_tiers,
+        )
+    }
+
+    /// Sets the loyalty curve boosting NFTs the longer they stay staked, from their next claim on
+    pub fn set_loyalty(
+        ctx: Context<SetLoyalty>,
+        loyalty: LoyaltyCurve,
+    ) -> Result<()> {
+        instructions::set_loyalty::handler(
+            ctx,
+            loyalty,
+        )
+    }
+
+    /// Sets the share of pending rewards forfeited when unstaking too early.
+    /// The treasury token account, if any, is passed as the first remaining account
+    pub fn set_early_unstake_penalty(
+        ctx: Context<SetEarlyUnstakePenalty>,
+        penalty: EarlyUnstakePenalty,
+    ) -> Result<()> {
+        instructions::set_early_unstake_penalty::handler(
+            ctx,
+            penalty,
+        )
+    }
+
+    /// Sets the fungible tokens stakers can deposit and their weight.
+    /// Staked positions move to the new weight the next time they are updated
+    pub fn set_token_weights(
+        ctx: Context<SetTokenWeights>,
+        token_weights: Vec<TokenWeight>,
+    ) -> Result<()> {
+        instructions::set_token_weights::handler(
+            ctx,
+            token_weights,
+        )
+    }
+
+    /// Sets the verified collection NFTs are checked against instead of the merkle allowlist,
+    /// and the rarity multiplier they stake with. The default key restores the allowlist
+    pub fn set_collection(
+        ctx: Context<SetCollection>,
+        collection: Pubkey,
+        collection_rarity: u64,
+    ) -> Result<()> {
+        instructions::set_collection::handler(
+            ctx,
+            collection,
+            collection_rarity,
+        )
+    }
+
+    /// Sets the first creator NFTs can be accepted through when it verified them, instead of the
+    /// merkle allowlist, and the rarity multiplier they stake with. The default key disables it
+    pub fn set_creator(
+        ctx: Context<SetCreator>,
+        creator: Pubkey,
+        creator_rarity: u64,
+    ) -> Result<()> {
+        instructions::set_creator::handler(
+            ctx,
+            creator,
+            creator_rarity,
+        )
+    }
+
+    /// Adds a merkle allowlist root to the project registry, created on first use.
+    /// The root takes the first retired slot, or a new one
+    pub fn add_root(
+        ctx: Context<AddRoot>,
+        name: [u8; 32],
+        root: [u8; 32],
+    ) -> Result<()> {
+        instructions::add_root::handler(
+            ctx,
+            name,
+            root,
+        )
+    }
+
+    /// Retires a merkle allowlist root of the project registry, its proofs are no longer accepted
+    pub fn retire_root(
+        ctx: Context<RetireRoot>,
+        index: u8,
+    ) -> Result<()> {
+        instructions::retire_root::handler(
+            ctx,
+            index,
+        )
+    }
+
+    /// Sets the time staked NFTs have to revalidate after the root changed
+    pub fn set_revalidation_grace(
+        ctx: Context<SetRevalidationGrace>,
+        revalidation_grace: i64,
+    ) -> Result<()> {
+        instructions::set_revalidation_grace::handler(
+            ctx,
+            revalidation_grace,
+        )
+    }
+
+    /// Sets the key signing rarity attestations, which stakers can bring instead of a proof.
+    /// The default key refuses attestations
+    pub fn set_rarity_authority(
+        ctx: Context<SetRarityAuthority>,
+        rarity_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::set_rarity_authority::handler(
+            ctx,
+            rarity_authority,
+        )
+    }
+
+    /// Writes the rarity multiplier of mints in the project rarity registry.
+    /// The `Rarity` account of each mint is passed as remaining accounts, in order, and created if needed
+    pub fn set_rarities<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetRarities<'info>>,
+        rarities: Vec<MintRarity>,
+    ) -> Result<()> {
+        instructions::set_rarities::handler(
+            ctx,
+            rarities,
+        )
+    }
+
+    /// Sets the groups of NFTs earning their own rate, told apart by collection or registry root.
+    /// Groups are matched by index, the ones with NFTs staked can't be removed
+    pub fn set_reward_groups(
+        ctx: Context<SetRewardGroups>,
+        reward_groups: Vec<RewardGroup>,
+    ) -> Result<()> {
+        instructions::set_reward_groups::handler(
+            ctx,
+            reward_groups,
+        )
+    }
+
+    /// Sets the rarity multiplier of a staked NFT to the one of the rarity registry.
+    /// Pending rewards are paid at the previous rarity first. Anyone can call it
+    pub fn refresh_rarity(ctx: Context<RefreshRarity>) -> Result<()> {
+        instructions::refresh_rarity::handler(ctx)
+    }
+
+    /// Proves again the rarity of a staked NFT after the root changed, paying its pending rewards
+    /// at the previous rarity. Once the grace period is over, NFTs that didn't revalidate stop
+    /// earning. Anyone can call it, the eligibility accounts are passed as remaining accounts
+    pub fn revalidate_stake(
+        ctx: Context<RevalidateStake>,
+        proof: Vec<[u8; 32]>,
+        root_index: Option<u8>,
+        rarity_multiplier: u64,
+    ) -> Result<()> {
+        instructions::revalidate_stake::handler(
+            ctx,
+            proof,
+            root_index,
+            rarity_multiplier,
         )
     }
 
@@ -60,68 +345,179 @@ mod staking {
         )
     }
 
-    /// Withdraw rewards from the vault
+    /// Withdraw rewards from the vault.
+    /// Can't go below the rewards owed to stakers unless `sunset` ends the staking
     pub fn withdraw_rewards(
         ctx: Context<WithdrawRewards>,
-        amount: u64
+        amount: u64,
+        sunset: bool,
     ) -> Result<()> {
         instructions::withdraw_rewards::handler(
             ctx,
-            amount
+            amount,
+            sunset,
         )
     }
 
-    /// Stake an NFT
+    /// Returns the days until the vault runs dry at the current emission
+    pub fn get_runway(ctx: Context<GetRunway>) -> Result<u64> {
+        instructions::get_runway::handler(ctx)
+    }
+
+    /// Stake an NFT.
+    /// The accounts proving the NFT belongs to the project are passed as remaining accounts, see `eligibility`
     pub fn stake_nft(
         ctx: Context<StakeNft>,
         bumps: StakedNftBumps,
         proof: Vec<[u8; 32]>,
         rarity_multiplier: u64,
+        lock_tier: Option<u8>,
+        root_index: Option<u8>,
     ) -> Result<()> {
-        instructions::stake_nft::handler(ctx, bumps, proof, rarity_multiplier)
+        instructions::stake_nft::handler(ctx, bumps, proof, rarity_multiplier, lock_tier, root_index)
     }
 
-    /// Stake an OCP NFT (ME royalties enforcement standard)
+    /// Unstake a NFT custodied by our escrow, using the SPL token program only
+    pub fn unstake_nft<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeNft<'info>>) -> Result<()> {
+        instructions::unstake_nft::handler(ctx)
+    }
+
+    /// Stake a NFT without custody, freezing it in the staker wallet (non programmable NFTs).
+    /// The accounts proving the NFT belongs to the project are passed as remaining accounts, see `eligibility`
+    pub fn stake_freeze(
+        ctx: Context<StakeFreeze>,
+        bumps: StakedNftBumps,
+        proof: Vec<[u8; 32]>,
+        rarity_multiplier: u64,
+        lock_tier: Option<u8>,
+        root_index: Option<u8>,
+    ) -> Result<()> {
+        instructions::stake_freeze::handler(ctx, bumps, proof, rarity_multiplier, lock_tier, root_index)
+    }
+
+    /// Unstake a NFT staked with stake_freeze, thawing it and revoking the delegate
+    pub fn unstake_freeze<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeFreeze<'info>>) -> Result<()> {
+        instructions::unstake_freeze::handler(ctx)
+    }
+
+    /// Stake a compressed NFT (Bubblegum), custodied by our escrow.
+    /// The accounts proving the NFT belongs to the project are passed as remaining accounts, see `eligibility`,
+    /// before the proof nodes
+    pub fn stake_cnft<'info>(
+        ctx: Context<'_, '_, '_, 'info, StakeCnft<'info>>,
+        bumps: StakedNftBumps,
+        proof: Vec<[u8; 32]>,
+        rarity_multiplier: u64,
+        lock_tier: Option<u8>,
+        leaf: CompressedLeaf,
+        root_index: Option<u8>,
+    ) -> Result<()> {
+        instructions::stake_cnft::handler(ctx, bumps, proof, rarity_multiplier, lock_tier, leaf, root_index)
+    }
+
+    /// Unstake a staked compressed NFT
+    pub fn unstake_cnft<'info>(
+        ctx: Context<'_, '_, '_, 'info, UnstakeCnft<'info>>,
+        leaf: CompressedLeaf,
+    ) -> Result<()> {
+        instructions::unstake_cnft::handler(ctx, leaf)
+    }
+
+    /// Stake a Metaplex Core asset, frozen in the staker wallet with the staking as freeze delegate.
+    /// The accounts proving the NFT belongs to the project are passed as remaining accounts, see `eligibility`
+    pub fn stake_core(
+        ctx: Context<StakeCore>,
+        bumps: StakedNftBumps,
+        proof: Vec<[u8; 32]>,
+        rarity_multiplier: u64,
+        lock_tier: Option<u8>,
+        root_index: Option<u8>,
+    ) -> Result<()> {
+        instructions::stake_core::handler(ctx, bumps, proof, rarity_multiplier, lock_tier, root_index)
+    }
+
+    /// Unstake a staked Metaplex Core asset, thawing it and removing the freeze delegate
+    pub fn unstake_core<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeCore<'info>>) -> Result<()> {
+        instructions::unstake_core::handler(ctx)
+    }
+
+    /// Stake an OCP NFT (ME royalties enforcement standard).
+    /// The accounts proving the NFT belongs to the project are passed as remaining accounts, see `eligibility`
     pub fn stake_ocp(
         ctx: Context<StakeOcp>,
         bumps: StakedNftBumps,
         proof: Vec<[u8; 32]>,
         rarity_multiplier: u64,
+        lock_tier: Option<u8>,
+        root_index: Option<u8>,
     ) -> Result<()> {
-        instructions::stake_ocp::handler(ctx, bumps, proof, rarity_multiplier)
+        instructions::stake_ocp::handler(ctx, bumps, proof, rarity_multiplier, lock_tier, root_index)
     }
 
     /// Unstake a staked OCP nft
-    pub fn unstake_ocp(ctx: Context<UnstakeOcp>) -> Result<()> {
+    pub fn unstake_ocp<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeOcp<'info>>) -> Result<()> {
         instructions::unstake_ocp::handler(ctx)
     }
 
-    /// Stake using Metaplex's Token Metadata program (support for pNFT)
+    /// Stake using Metaplex's Token Metadata program (support for pNFT).
+    /// The accounts proving the NFT belongs to the project are passed as remaining accounts, see `eligibility`
     pub fn stake_mpl(
         ctx: Context<StakeMpl>,
         bumps: StakedNftBumps,
         proof: Vec<[u8; 32]>,
         rarity_multiplier: u64,
+        lock_tier: Option<u8>,
+        root_index: Option<u8>,
     ) -> Result<()> {
-        instructions::stake_mpl::handler(ctx, bumps, proof, rarity_multiplier)
+        instructions::stake_mpl::handler(ctx, bumps, proof, rarity_multiplier, lock_tier, root_index)
     }
 
     /// Unstake using Metaplex's Token Metadata program (support for pNFT)
-    pub fn unstake_mpl(ctx: Context<UnstakeMpl>) -> Result<()> {
+    pub fn unstake_mpl<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeMpl<'info>>) -> Result<()> {
         instructions::unstake_mpl::handler(ctx)
     }
 
     /// Unstake using Metaplex's Token Metadata program a token custodied by our escrow
     /// Mainly used to support non pNFT staked collections migrating to pNFT
-    pub fn unstake_mpl_custodial(ctx: Context<UnstakeMplCustodial>) -> Result<()> {
+    pub fn unstake_mpl_custodial<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeMplCustodial<'info>>) -> Result<()> {
         instructions::unstake_mpl_custodial::handler(ctx)
     }
 
+    /// Stake an amount of a fungible token into the project vault
+    pub fn stake_tokens(ctx: Context<StakeTokens>, amount: u64) -> Result<()> {
+        instructions::stake_tokens::handler(ctx, amount)
+    }
+
+    /// Unstake an amount of staked fungible tokens
+    pub fn unstake_tokens(ctx: Context<UnstakeTokens>, amount: u64) -> Result<()> {
+        instructions::unstake_tokens::handler(ctx, amount)
+    }
+
+    /// Claim the rewards of a fungible token position
+    pub fn claim_position(ctx: Context<ClaimPosition>) -> Result<()> {
+        instructions::claim_position::handler(ctx)
+    }
+
     /// Claim staking rewards
     pub fn claim_staking(ctx: Context<ClaimStaking>) -> Result<()> {
         instructions::claim_staking::handler(ctx)
     }
 
+    /// Claim rewards the vault could not pay in full before
+    pub fn claim_owed_rewards(ctx: Context<ClaimOwedRewards>) -> Result<()> {
+        instructions::claim_owed_rewards::handler(ctx)
+    }
+
+    /// Resizes a Staking account created with a previous layout
+    pub fn migrate_staking(ctx: Context<MigrateStaking>) -> Result<()> {
+        instructions::migrate_staking::handler(ctx)
+    }
+
+    /// Resizes a StakedNft account created with a previous layout and registers its weight
+    pub fn migrate_staked_nft(ctx: Context<MigrateStakedNft>) -> Result<()> {
+        instructions::migrate_staked_nft::handler(ctx)
+    }
+
     /*
     /// Migrate Escrow account
     pub fn migrate_escrow(ctx: Context<MigrateEscrow>) -> Result<()> {
@@ -172,7 +568,269 @@ pub struct Staking {
     pub root: [u8; 32],
 
     /// Whether or not the project is fees exempt: default to false
-    pub fees_exempt: bool
+    pub fees_exempt: bool,
+
+    /// How daily rewards are emitted to the staked NFTs
+    pub reward_mode: RewardMode,
+
+    /// The sum of the weights of all NFTs registered in the reward pool
+    pub total_weight: u64,
+
+    /// Rewards accumulated per unit of weight since the start, scaled by `rewards::PRECISION`
+    pub acc_reward_per_weight: u128,
+
+    /// Last time project wide rewards were accrued
+    pub last_update: i64,
+
+    /// The time the staking ends (in seconds since 1970), if any
+    pub end: Option<i64>,
+
+    /// Campaign windows rewards accrue in, sorted. Rewards accrue all the time if empty
+    pub windows: Vec<StakingWindow>,
+
+    /// The rewards owed to stakers the vault could not pay in full
+    pub total_owed: u64,
+
+    /// The rewards accrued by the staked NFTs and not claimed yet
+    pub accrued_liability: u64,
+
+    /// Lock periods stakers can commit to in exchange for a boost
+    pub lock_tiers: Vec<LockTier>,
+
+    /// Boost earned by NFTs the longer they stay staked
+    pub loyalty: LoyaltyCurve,
+
+    /// Share of pending rewards forfeited when unstaking too early
+    pub early_unstake_penalty: EarlyUnstakePenalty,
+
+    /// Fungible tokens stakers can deposit, with the weight they earn
+    pub token_weights: Vec<TokenWeight>,
+
+    /// The verified Metaplex collection NFTs must belong to. The merkle allowlist is used if default
+    pub collection: Pubkey,
+
+    /// The rarity multiplier of NFTs staked through the collection
+    pub collection_rarity: u64,
+
+    /// The verified first creator NFTs can be accepted through. Not checked if default
+    pub creator: Pubkey,
+
+    /// The rarity multiplier of NFTs staked through the creator
+    pub creator_rarity: u64,
+
+    /// The ed25519 key signing rarity attestations. Attestations are refused if default
+    pub rarity_authority: Pubkey,
+
+    /// Last time the root was replaced
+    pub root_updated_at: i64,
+
+    /// Time staked NFTs have after a root change to revalidate, before they can be revoked (in seconds)
+    pub revalidation_grace: i64,
+
+    /// Groups of NFTs earning their own share of `daily_rewards`, told apart by collection or root
+    pub reward_groups: Vec<RewardGroup>,
+
+    /// The wallet proposed to own the staking, until it accepts. None if default
+    pub pending_owner: Pubkey,
+
+    /// Wallets granted administration permissions by the owner
+    pub roles: Vec<Role>,
+
+    /// Team members approving the sensitive actions of the project
+    pub approvers: Vec<Pubkey>,
+
+    /// Approvals a proposal needs to execute. Sensitive actions need no approval if zero
+    pub approval_threshold: u8,
+
+    /// Rewards that can be withdrawn without approval, per day
+    pub approval_limit: u64,
+
+    /// Changes with the approvers, expiring the proposals of the previous ones
+    pub approvers_nonce: u64,
+
+    /// Proposals created so far, deriving the next proposal address
+    pub proposal_count: u64,
+
+    /// Start of the current period of withdrawals without approval
+    pub withdrawal_period_start: i64,
+
+    /// Rewards withdrawn without approval in the current period
+    pub withdrawn_in_period: u64,
+}
+
+impl Staking {
+    pub const MAX_WINDOWS: usize = 8;
+    pub const MAX_LOCK_TIERS: usize = 8;
+    pub const MAX_TOKEN_WEIGHTS: usize = 8;
+    pub const MAX_REWARD_GROUPS: usize = 4;
+    pub const MAX_ROLES: usize = 8;
+    pub const MAX_APPROVERS: usize = 8;
+
+    pub const LEN: usize = 8 + 32 + 32 + 3 + 32 + 32 + 32 + 8 + 8 + 8 + 32 + 1
+        + 1 + 8 + 16 + 8
+        + 1 + 8
+        + 4 + Staking::MAX_WINDOWS * StakingWindow::LEN
+        + 8 + 8
+        + 4 + Staking::MAX_LOCK_TIERS * LockTier::LEN
+        + LoyaltyCurve::LEN
+        + EarlyUnstakePenalty::LEN
+        + 4 + Staking::MAX_TOKEN_WEIGHTS * TokenWeight::LEN
+        + 32 + 8
+        + 32 + 8
+        + 32
+        + 8 + 8
+        + 4 + Staking::MAX_REWARD_GROUPS * RewardGroup::LEN
+        + 32
+        + 4 + Staking::MAX_ROLES * Role::LEN
+        + 4 + Staking::MAX_APPROVERS * 32 + 1 + 8 + 8 + 8
+        + 8 + 8;
+}
+
+/// A period of time rewards accrue in
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StakingWindow {
+    /// The time the window opens (in seconds since 1970)
+    pub start: i64,
+
+    /// The time the window closes (in seconds since 1970)
+    pub end: i64,
+}
+
+impl StakingWindow {
+    pub const LEN: usize = 8 + 8;
+}
+
+/// A lock period a staker can commit an NFT to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct LockTier {
+    /// How long the NFT can't be unstaked (in seconds)
+    pub duration: i64,
+
+    /// Extra weight granted while staked, in percent of the rarity multiplier
+    pub boost: u64,
+}
+
+impl LockTier {
+    pub const LEN: usize = 8 + 8;
+}
+
+/// A boost growing by `step_boost` every `step_duration` staked, up to `max_boost`.
+/// Disabled while `step_duration` is zero
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct LoyaltyCurve {
+    /// Continuous staking time needed for each step (in seconds)
+    pub step_duration: i64,
+
+    /// Extra weight granted by each step, in percent of the rarity multiplier
+    pub step_boost: u64,
+
+    /// The highest boost the curve can reach, in percent of the rarity multiplier
+    pub max_boost: u64,
+}
+
+impl LoyaltyCurve {
+    pub const MAX_STEPS: u64 = 36;
+
+    pub const LEN: usize = 8 + 8 + 8;
+}
+
+/// A share of pending rewards forfeited by NFTs unstaked before `min_duration`.
+/// Forfeited rewards go to `treasury`, or stay in the rewards account if it is the default key
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct EarlyUnstakePenalty {
+    /// Staking time under which the penalty applies (in seconds)
+    pub min_duration: i64,
+
+    /// Share of pending rewards forfeited, in percent
+    pub rate: u64,
+
+    /// The token account receiving forfeited rewards
+    pub treasury: Pubkey,
+}
+
+impl EarlyUnstakePenalty {
+    pub const LEN: usize = 8 + 8 + 32;
+}
+
+/// A fungible token stakers can deposit, and the weight it earns
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct TokenWeight {
+    /// The mint of the token
+    pub mint: Pubkey,
+
+    /// Weight earned per whole token staked, in the unit of the rarity multiplier
+    pub weight: u64,
+}
+
+impl TokenWeight {
+    pub const LEN: usize = 32 + 8;
+}
+
+/// NFTs of a collection, or of an allowlist of the root registry, earning their own rate
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RewardGroup {
+    /// The verified collection of the group. Default for a group of allowlisted NFTs
+    pub collection: Pubkey,
+
+    /// The index of the registry root listing the group, if it isn't a collection group
+    pub root_index: Option<u8>,
+
+    /// The rewards earned by the NFTs of the group, in percent of `daily_rewards`
+    pub rate: u64,
+
+    /// The most NFTs of the group staked at once. Unlimited if zero
+    pub cap: u64,
+
+    /// The NFTs of the group currently staked. Kept by the program
+    pub nfts_staked: u64,
+}
+
+impl RewardGroup {
+    pub const LEN: usize = 32 + 2 + 8 + 8 + 8;
+}
+
+/// A wallet and the administration permissions it holds, as `roles` bits
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct Role {
+    /// The wallet
+    pub key: Pubkey,
+
+    /// The permission bits granted to the wallet
+    pub permissions: u8,
+}
+
+impl Role {
+    pub const LEN: usize = 32 + 1;
+}
+
+/// How a project distributes its `daily_rewards`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum RewardMode {
+    /// Every staked NFT earns `daily_rewards` adjusted by its rarity
+    #[default]
+    PerNft,
+
+    /// `daily_rewards` is emitted in total and split pro rata by rarity weight
+    SharedPool,
+}
+
+/// A compressed NFT leaf, as hashed in its concurrent merkle tree
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct CompressedLeaf {
+    /// The current root of the concurrent merkle tree
+    pub root: [u8; 32],
+
+    /// Hash of the NFT metadata
+    pub data_hash: [u8; 32],
+
+    /// Hash of the NFT creators
+    pub creator_hash: [u8; 32],
+
+    /// The nonce the NFT was minted with, deriving its asset id
+    pub nonce: u64,
+
+    /// Position of the leaf in the tree
+    pub index: u32,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
@@ -191,7 +849,7 @@ pub struct StakedNft {
     /// Staking key identifying the project
     pub key: Pubkey,
 
-    /// The mint of the NFT
+    /// The mint of the NFT, or its asset id for compressed NFTs
     pub mint: Pubkey,
 
     /// Owner of the staked NFT
@@ -205,8 +863,284 @@ pub struct StakedNft {
 
     /// Last time the owner claimed rewards
     pub last_claim: i64,
+
+    /// Weight registered in the project reward pool
+    pub weight: u64,
+
+    /// Rewards already accounted for, scaled by `rewards::PRECISION`
+    pub reward_debt: u128,
+
+    /// The NFT can't be unstaked before this time (in seconds since 1970)
+    pub locked_until: i64,
+
+    /// Extra weight granted by the lock tier, in percent of the rarity multiplier
+    pub lock_boost: u64,
+
+    /// Last time the NFT proved its rarity again with `revalidate_stake` or `refresh_rarity`
+    pub validated_at: i64,
+
+    /// The index of the reward group the NFT earns in, if any
+    pub group: Option<u8>,
+
+    /// Rewards accrued before the stake was migrated, paid with its next claim
+    pub pending_rewards: u64,
 }
 
 impl StakedNft {
-    pub const LEN: usize = 8 + 2 + 32 + 32 + 32 + 8 + 8 + 8;
+    pub const LEN: usize = 8 + 2 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 16 + 8 + 8 + 8 + 2 + 8;
+}
+
+/// The fungible tokens of a mint staked by a staker in a project
+#[account]
+#[derive(Default)]
+pub struct StakedPosition {
+    /// Bump used to create this PDA
+    pub bump: u8,
+
+    /// Staking key identifying the project
+    pub key: Pubkey,
+
+    /// Owner of the staked tokens
+    pub staker: Pubkey,
+
+    /// The mint of the staked tokens
+    pub mint: Pubkey,
+
+    /// Decimals of the mint, a whole token earns the mint weight
+    pub decimals: u8,
+
+    /// The amount of tokens staked
+    pub amount: u64,
+
+    /// Creation of this position
+    pub staked_at: i64,
+
+    /// Last time the rewards of the position were accrued
+    pub last_claim: i64,
+
+    /// Weight registered in the project reward pool
+    pub weight: u64,
+
+    /// Pool rewards already accounted for, scaled by `rewards::PRECISION`
+    pub reward_debt: u128,
+
+    /// Rewards accrued by the position and not claimed yet
+    pub pending_rewards: u64,
+}
+
+impl StakedPosition {
+    pub const LEN: usize = 8 + 1 + 32 + 32 + 32 + 1 + 8 + 8 + 8 + 8 + 16 + 8;
+}
+
+/// The rewards owed to a staker of a project when the vault could not pay in full
+#[account]
+#[derive(Default)]
+pub struct OwedRewards {
+    /// Bump used to create this PDA
+    pub bump: u8,
+
+    /// Staking key identifying the project
+    pub key: Pubkey,
+
+    /// The staker the rewards are owed to
+    pub staker: Pubkey,
+
+    /// The amount of reward tokens owed
+    pub amount: u64,
+}
+
+impl OwedRewards {
+    pub const LEN: usize = 8 + 1 + 32 + 32 + 8;
+}
+
+/// A merkle allowlist root of a project, next to `Staking.root`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct NamedRoot {
+    /// Label of the root (a drop, a collection...), UTF-8 padded with zeros
+    pub name: [u8; 32],
+
+    /// The root of the merkle tree
+    pub root: [u8; 32],
+
+    /// Retired roots no longer accept proofs, and their slot can be reused
+    pub active: bool,
+}
+
+impl NamedRoot {
+    pub const LEN: usize = 32 + 32 + 1;
+}
+
+/// The merkle allowlist roots of a project, on top of `Staking.root`.
+/// Stakers pass the index of the root their proof is for
+#[account]
+#[derive(Default)]
+pub struct RootRegistry {
+    /// Bump used to create this PDA
+    pub bump: u8,
+
+    /// Staking key identifying the project
+    pub key: Pubkey,
+
+    /// The roots, a root keeps its index until retired
+    pub roots: Vec<NamedRoot>,
+}
+
+impl RootRegistry {
+    pub const MAX_ROOTS: usize = 16;
+
+    pub const LEN: usize = 8 + 1 + 32 + 4 + RootRegistry::MAX_ROOTS * NamedRoot::LEN;
+}
+
+/// The rarity multiplier of a mint in a project, written by the owner.
+/// Stands for the allowlist proof when passed to the stake instructions
+#[account]
+#[derive(Default)]
+pub struct Rarity {
+    /// Bump used to create this PDA
+    pub bump: u8,
+
+    /// Staking key identifying the project
+    pub key: Pubkey,
+
+    /// The mint, or the asset id, the rarity is for
+    pub mint: Pubkey,
+
+    /// The rarity multiplier of the mint, zero if it can't be staked
+    pub rarity_multiplier: u64,
+}
+
+impl Rarity {
+    pub const LEN: usize = 8 + 1 + 32 + 32 + 8;
+}
+
+/// A rarity multiplier to write in the rarity registry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct MintRarity {
+    /// The mint, or the asset id
+    pub mint: Pubkey,
+
+    /// The rarity multiplier
+    pub rarity_multiplier: u64,
+}
+
+/// Staking parameters to change with `update_staking`. Fields left to None are untouched
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct StakingUpdate {
+    /// The amount of tokens rewarded daily per NFT
+    pub daily_rewards: Option<u64>,
+
+    /// The time the staking starts (in seconds since 1970)
+    pub start: Option<i64>,
+
+    /// The root of the merkle allowlist
+    pub root: Option<[u8; 32]>,
+
+    /// The time the staking ends (in seconds since 1970). Only `set_staking` can remove it
+    pub end: Option<i64>,
+}
+
+impl StakingUpdate {
+    pub const LEN: usize = (1 + 8) + (1 + 8) + (1 + 32) + (1 + 8);
+}
+
+/// A sensitive action waiting for the approval of the project approvers
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum ProposedAction {
+    /// Withdraws rewards to the `destination` token account, like `withdraw_rewards`
+    WithdrawRewards {
+        amount: u64,
+        sunset: bool,
+        destination: Pubkey,
+    },
+
+    /// Changes staking parameters, like `update_staking`
+    UpdateStaking {
+        update: StakingUpdate,
+    },
+
+    /// Replaces the approvers, their threshold and the limit of withdrawals without approval
+    SetApprovers {
+        approvers: Vec<Pubkey>,
+        approval_threshold: u8,
+        approval_limit: u64,
+    },
+
+    /// Adds a merkle root to the registry, like `add_root`
+    AddRoot {
+        name: [u8; 32],
+        root: [u8; 32],
+    },
+
+    /// Retires a merkle root of the registry, like `retire_root`
+    RetireRoot {
+        index: u8,
+    },
+
+    /// Sets the collection rule, like `set_collection`
+    SetCollection {
+        collection: Pubkey,
+        collection_rarity: u64,
+    },
+
+    /// Sets the creator rule, like `set_creator`
+    SetCreator {
+        creator: Pubkey,
+        creator_rarity: u64,
+    },
+
+    /// Writes rarity multipliers, like `set_rarities`
+    SetRarities {
+        rarities: Vec<MintRarity>,
+    },
+
+    /// Sets the key signing rarity attestations, like `set_rarity_authority`
+    SetRarityAuthority {
+        rarity_authority: Pubkey,
+    },
+
+    /// Sets the reward groups, like `set_reward_groups`
+    SetRewardGroups {
+        reward_groups: Vec<RewardGroup>,
+    },
+}
+
+impl Default for ProposedAction {
+    fn default() -> Self {
+        ProposedAction::UpdateStaking { update: StakingUpdate::default() }
+    }
+}
+
+impl ProposedAction {
+    /// Rarities a single proposal can write
+    pub const MAX_RARITIES: usize = 6;
+
+    /// Size of the largest variant
+    pub const LEN: usize = 1 + 4 + Staking::MAX_APPROVERS * 32 + 1 + 8;
+}
+
+/// A sensitive action proposed by an approver, executed once enough approvers approved it
+#[account]
+#[derive(Default)]
+pub struct Proposal {
+    /// Bump used to create this PDA
+    pub bump: u8,
+
+    /// Staking key identifying the project
+    pub key: Pubkey,
+
+    /// The approver who proposed the action, refunded when it executes
+    pub proposer: Pubkey,
+
+    /// The action to execute
+    pub action: ProposedAction,
+
+    /// The approvers who approved the action, as bits of their index in `Staking.approvers`
+    pub approvals: u8,
+
+    /// The `Staking.approvers_nonce` of the approvers the proposal is for
+    pub approvers_nonce: u64,
+}
+
+impl Proposal {
+    pub const LEN: usize = 8 + 1 + 32 + 32 + ProposedAction::LEN + 1 + 8;
 }
@@ -0,0 +1,103 @@
+//! Minimal client for Metaplex Core assets.
+//! Only builds the freeze delegate plugin instructions used to lock staked assets in place and
+//! reads the collection of an asset
+
+use anchor_lang::prelude::*;
+use solana_program::instruction::{AccountMeta, Instruction};
+
+solana_program::declare_id!("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+
+/// Instruction discriminators
+const ADD_PLUGIN_V1: u8 = 2;
+const REMOVE_PLUGIN_V1: u8 = 4;
+const UPDATE_PLUGIN_V1: u8 = 6;
+
+/// Index of the freeze delegate in the plugin and plugin type enums
+const FREEZE_DELEGATE: u8 = 1;
+
+/// Index of the address variant in the plugin authority enum
+const AUTHORITY_ADDRESS: u8 = 3;
+
+/// Key of asset accounts
+const ASSET_V1: u8 = 1;
+
+/// Index of the collection variant in the update authority enum
+const UPDATE_AUTHORITY_COLLECTION: u8 = 2;
+
+/// Accounts shared by the plugin instructions. Absent optional accounts are the Core program id
+fn plugin_accounts(asset: Pubkey, collection: Pubkey, payer: Pubkey, authority: Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(asset, false),
+        if collection == ID {
+            AccountMeta::new_readonly(collection, false)
+        } else {
+            AccountMeta::new(collection, false)
+        },
+        AccountMeta::new(payer, true),
+        AccountMeta::new_readonly(authority, true),
+        AccountMeta::new_readonly(solana_program::system_program::ID, false),
+        AccountMeta::new_readonly(ID, false),
+    ]
+}
+
+/// Adds a frozen freeze delegate plugin to `asset`, with `delegate` as its authority.
+/// `authority` must be the asset owner
+pub fn add_freeze_delegate(
+    asset: Pubkey,
+    collection: Pubkey,
+    payer: Pubkey,
+    authority: Pubkey,
+    delegate: Pubkey,
+) -> Instruction {
+    let mut data = vec![ADD_PLUGIN_V1, FREEZE_DELEGATE, true as u8, 1, AUTHORITY_ADDRESS];
+    data.extend(delegate.to_bytes());
+
+    Instruction {
+        program_id: ID,
+        accounts: plugin_accounts(asset, collection, payer, authority),
+        data,
+    }
+}
+
+/// Freezes or thaws `asset`. `authority` must be the freeze delegate authority
+pub fn update_freeze_delegate(
+    asset: Pubkey,
+    collection: Pubkey,
+    payer: Pubkey,
+    authority: Pubkey,
+    frozen: bool,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: plugin_accounts(asset, collection, payer, authority),
+        data: vec![UPDATE_PLUGIN_V1, FREEZE_DELEGATE, frozen as u8],
+    }
+}
+
+/// Removes the freeze delegate plugin of a thawed `asset`. `authority` must be the asset owner
+pub fn remove_freeze_delegate(
+    asset: Pubkey,
+    collection: Pubkey,
+    payer: Pubkey,
+    authority: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: plugin_accounts(asset, collection, payer, authority),
+        data: vec![REMOVE_PLUGIN_V1, FREEZE_DELEGATE],
+    }
+}
+
+/// Returns the collection of `asset`, stored as its update authority, or None if the account
+/// isn't a Core asset or the asset has no collection
+pub fn collection_of(asset: &AccountInfo) -> Option<Pubkey> {
+    if *asset.owner != ID {
+        return None;
+    }
+    // key (1), owner (32), update authority variant (1) and address (32)
+    let data = asset.data.borrow();
+    if data.len() < 66 || data[0] != ASSET_V1 || data[33] != UPDATE_AUTHORITY_COLLECTION {
+        return None;
+    }
+    Some(Pubkey::new_from_array(data[34..66].try_into().ok()?))
+}
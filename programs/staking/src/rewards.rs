@@ -0,0 +1,633 @@
+//! Reward accounting shared by the stake, claim and unstake instructions.
+//! A project either pays `daily_rewards` to every staked NFT, adjusted by its rarity,
+//! or emits `daily_rewards` in total and splits it pro rata by rarity weight.
+//! Both modes accrue a global reward per weight index, each stake snapshots it as its reward debt.
+//! NFTs of a reward group earn the rate of their group, in percent of `daily_rewards`.
+
+use anchor_lang::prelude::*;
+
+use crate::{LockTier, LoyaltyCurve, OwedRewards, RewardMode, StakedNft, StakedPosition, Staking, StakingWindow};
+use crate::errors::StakingError;
+use crate::token_interface;
+
+/// Seconds in a day, the period `daily_rewards` is expressed in
+pub const SECONDS_PER_DAY: u128 = 86400;
+
+/// Rarity multiplier of an NFT earning exactly `daily_rewards`
+pub const BASE_WEIGHT: u128 = 100;
+
+/// Rate of a reward group earning exactly `daily_rewards`
+pub const GROUP_BASE_RATE: u64 = 100;
+
+/// Fixed point precision of the reward per weight index
+pub const PRECISION: u128 = 1_000_000_000_000;
+
+impl Staking {
+    /// Returns `daily_rewards` integrated between `from` and `to`, in tokens times seconds per day,
+    /// only while the staking is active. Every change of the rate accrues first, so the current
+    /// rate is the one in force since `last_update`
+    pub fn emission_between(&self, from: i64, to: i64) -> Result<u128> {
+        let from = from.max(self.start);
+        let to = self.end.map_or(to, |end| to.min(end));
+        if to <= from {
+            return Ok(0);
+        }
+
+        (self.daily_rewards as u128)
+            .checked_mul(self.active_seconds(from, to))
+            .ok_or_else(|| error!(StakingError::MathOverflow))
+    }
+
+    /// Returns the seconds between `from` and `to` covered by the campaign windows
+    fn active_seconds(&self, from: i64, to: i64) -> u128 {
+        if self.windows.is_empty() {
+            return (to - from) as u128;
+        }
+
+        self.windows
+            .iter()
+            .map(|window| {
+                let start = window.start.max(from);
+                let end = window.end.min(to);
+                if end > start { (end - start) as u128 } else { 0 }
+            })
+            .sum()
+    }
+
+    /// Returns true once the staking end has passed
+    pub fn has_ended(&self, now: i64) -> bool {
+        self.end.is_some_and(|end| end <= now)
+    }
+
+    /// Changes the staking end, which can't move once passed nor be set in the past
+    pub fn set_end(&mut self, end: Option<i64>, now: i64) -> Result<()> {
+        if end == self.end {
+            return Ok(());
+        }
+        if self.has_ended(now) || end.is_some_and(|end| end < now) {
+            return err!(StakingError::InvalidSchedule);
+        }
+
+        self.accrue(now)?;
+        self.end = end;
+
+        Ok(())
+    }
+
+    /// Changes the staking start. While anything is staked, it can't move once passed nor be
+    /// set in the past, as it would reprice the rewards not claimed yet
+    pub fn set_start(&mut self, start: i64, now: i64) -> Result<()> {
+        if start == self.start {
+            return Ok(());
+        }
+        let staked = self.nfts_staked > 0 || self.total_weight > 0;
+        if staked && (self.start <= now || start < now) {
+            return err!(StakingError::InvalidSchedule);
+        }
+
+        self.accrue(now)?;
+        self.start = start;
+
+        Ok(())
+    }
+
+    /// Replaces the campaign windows from `now` on. Rewards are accrued first, so the windows
+    /// that already elapsed can be dropped
+    pub fn set_windows(&mut self, windows: Vec<StakingWindow>, now: i64) -> Result<()> {
+        if windows.len() > Staking::MAX_WINDOWS {
+            return err!(StakingError::InvalidSchedule);
+        }
+
+        // Windows must be sorted and must not overlap
+        let mut previous_end = i64::MIN;
+        for window in windows.iter() {
+            if window.start >= window.end || window.start < previous_end {
+                return err!(StakingError::InvalidSchedule);
+            }
+            previous_end = window.end;
+        }
+
+        self.accrue(now)?;
+        self.windows = windows;
+
+        Ok(())
+    }
+
+    /// Changes `daily_rewards` from `now` on, keeping the previous rate for the time already elapsed
+    pub fn set_daily_rewards(&mut self, daily_rewards: u64, now: i64) -> Result<()> {
+        if daily_rewards == self.daily_rewards {
+            return Ok(());
+        }
+
+        // Accrue rewards at the previous rate before changing it
+        self.accrue(now)?;
+        self.daily_rewards = daily_rewards;
+
+        Ok(())
+    }
+
+    /// Accrues project wide rewards up to `now`: the reward per weight index and the liability
+    pub fn accrue(&mut self, now: i64) -> Result<()> {
+        if now <= self.last_update {
+            return Ok(());
+        }
+
+        // Nothing is emitted while no NFT is staked
+        if self.total_weight > 0 {
+            let emission = self.emission_between(self.last_update, now)?;
+            let emission = emission
+                .checked_mul(PRECISION)
+                .ok_or_else(|| error!(StakingError::MathOverflow))?
+                / SECONDS_PER_DAY;
+            let increment = match self.reward_mode {
+                RewardMode::PerNft => emission / BASE_WEIGHT,
+                RewardMode::SharedPool => emission / self.total_weight as u128,
+            };
+            self.acc_reward_per_weight = self.acc_reward_per_weight
+                .checked_add(increment)
+                .ok_or_else(|| error!(StakingError::MathOverflow))?;
+            let accrued = increment
+                .checked_mul(self.total_weight as u128)
+                .ok_or_else(|| error!(StakingError::MathOverflow))?
+                / PRECISION;
+            self.accrued_liability = self.accrued_liability
+                .saturating_add(u64::try_from(accrued).unwrap_or(u64::MAX));
+        }
+        self.last_update = now;
+
+        Ok(())
+    }
+
+    /// Returns the rewards accrued but not paid yet, owed rewards included
+    pub fn liability(&self) -> u64 {
+        self.accrued_liability.saturating_add(self.total_owed)
+    }
+
+    /// Returns the tokens emitted per day by the staked NFTs while the staking is active
+    fn active_daily_emission(&self) -> u128 {
+        match self.reward_mode {
+            RewardMode::PerNft => {
+                self.daily_rewards as u128 * self.total_weight as u128 / BASE_WEIGHT
+            }
+            RewardMode::SharedPool if self.total_weight > 0 => self.daily_rewards as u128,
+            RewardMode::SharedPool => 0,
+        }
+    }
+
+    /// Returns the tokens emitted per day by the staked NFTs at `now`, zero before the start,
+    /// after the end or outside the campaign windows
+    pub fn daily_emission(&self, now: i64) -> Result<u128> {
+        if self.emission_between(now, now.saturating_add(1))? == 0 {
+            return Ok(0);
+        }
+
+        Ok(self.active_daily_emission())
+    }
+
+    /// Returns the tokens the staked NFTs earn between `from` and `to` at their current weight
+    fn emission_of_stakes(&self, from: i64, to: i64) -> Result<u128> {
+        let emission = self.emission_between(from, to)?;
+        let emitted = match self.reward_mode {
+            RewardMode::PerNft => {
+                emission
+                    .checked_mul(self.total_weight as u128)
+                    .ok_or_else(|| error!(StakingError::MathOverflow))?
+                    / BASE_WEIGHT
+            }
+            RewardMode::SharedPool if self.total_weight > 0 => emission,
+            RewardMode::SharedPool => 0,
+        };
+
+        Ok(emitted / SECONDS_PER_DAY)
+    }
+
+    /// Returns the days from `now` until a vault holding `vault_amount` runs dry, once the
+    /// liability is paid. Rewards only accrue until the end and within the campaign windows.
+    /// None if the vault covers everything the staked NFTs will earn
+    pub fn runway_days(&self, vault_amount: u64, now: i64) -> Result<Option<u64>> {
+        let active_daily_emission = self.active_daily_emission();
+        if active_daily_emission == 0 {
+            return Ok(None);
+        }
+        let free_amount = vault_amount.saturating_sub(self.liability()) as u128;
+
+        // Nothing is emitted after the end or the last window
+        let last_window = self.windows.iter().map(|window| window.end).max();
+        let horizon = match (self.end, last_window) {
+            (Some(end), Some(last_window)) => Some(end.min(last_window)),
+            (end, last_window) => end.or(last_window),
+        };
+
+        let dry_at = match horizon {
+            Some(horizon) => {
+                if self.emission_of_stakes(now, horizon)? <= free_amount {
+                    return Ok(None);
+                }
+
+                // First time the emission since now exceeds the free amount
+                let (mut low, mut high) = (now, horizon);
+                while high - low > 1 {
+                    let middle = low + (high - low) / 2;
+                    if self.emission_of_stakes(now, middle)? > free_amount {
+                        high = middle;
+                    } else {
+                        low = middle;
+                    }
+                }
+                high
+            }
+            None => {
+                let seconds = free_amount
+                    .checked_mul(SECONDS_PER_DAY)
+                    .ok_or_else(|| error!(StakingError::MathOverflow))?
+                    / active_daily_emission;
+                now.max(self.start).saturating_add(i64::try_from(seconds).unwrap_or(i64::MAX))
+            }
+        };
+
+        Ok(Some((dry_at.saturating_sub(now) / SECONDS_PER_DAY as i64) as u64))
+    }
+
+    /// Returns the part of `rewards_amount` forfeited by unstaking `staked_nft` at `now`
+    pub fn early_unstake_forfeit(&self, staked_nft: &StakedNft, rewards_amount: u64, now: i64) -> u64 {
+        let penalty = &self.early_unstake_penalty;
+        if now.saturating_sub(staked_nft.staked_at) >= penalty.min_duration {
+            return 0;
+        }
+
+        (rewards_amount as u128 * penalty.rate.min(100) as u128 / 100) as u64
+    }
+
+    /// Returns the weight earned by `amount` of a fungible token with `decimals`.
+    /// Tokens no longer weighted by the project earn nothing
+    pub fn token_weight(&self, mint: &Pubkey, amount: u64, decimals: u8) -> Result<u64> {
+        let weight = match self.token_weights.iter().find(|token| token.mint == *mint) {
+            Some(token) => token.weight,
+            None => return Ok(0),
+        };
+        let unit = 10u128
+            .checked_pow(decimals as u32)
+            .ok_or_else(|| error!(StakingError::MathOverflow))?;
+        let weight = (amount as u128)
+            .checked_mul(weight as u128)
+            .ok_or_else(|| error!(StakingError::MathOverflow))?
+            / unit;
+
+        u64::try_from(weight).map_err(|_| error!(StakingError::MathOverflow))
+    }
+
+    /// Returns the rate of the reward `group`, in percent of `daily_rewards`.
+    /// NFTs out of any group earn `daily_rewards`
+    pub fn group_rate(&self, group: Option<u8>) -> u64 {
+        match group.and_then(|index| self.reward_groups.get(index as usize)) {
+            Some(group) => group.rate,
+            None => GROUP_BASE_RATE,
+        }
+    }
+
+    /// Returns the rewards accumulated by `weight` since the start, scaled by `PRECISION`
+    fn pool_share(&self, weight: u64) -> Result<u128> {
+        (weight as u128)
+            .checked_mul(self.acc_reward_per_weight)
+            .ok_or_else(|| error!(StakingError::MathOverflow))
+    }
+}
+
+impl LoyaltyCurve {
+    /// Checks the curve is disabled, or reaches its cap in a bounded number of steps
+    pub fn validate(&self) -> Result<()> {
+        if self.step_duration == 0 {
+            return Ok(());
+        }
+        if self.step_duration < 0
+            || self.step_boost == 0
+            || self.max_boost / self.step_boost > LoyaltyCurve::MAX_STEPS {
+            return err!(StakingError::InvalidLoyaltyCurve);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the boost earned after being staked for `staked_for` seconds
+    pub fn boost_at(&self, staked_for: i64) -> u64 {
+        if self.step_duration <= 0 || staked_for <= 0 {
+            return 0;
+        }
+
+        let steps = (staked_for / self.step_duration) as u64;
+        steps.saturating_mul(self.step_boost).min(self.max_boost)
+    }
+}
+
+impl StakedNft {
+    /// Returns the rarity multiplier boosted by the lock tier and by the loyalty earned at `at`,
+    /// at the rate of the reward group of the NFT
+    pub fn multiplier_at(&self, staking: &Staking, at: i64) -> Result<u64> {
+        let boost = BASE_WEIGHT + self.lock_boost as u128 + staking.loyalty.boost_at(at - self.staked_at) as u128;
+        let boosted = (self.rarity_multiplier as u128)
+            .checked_mul(boost)
+            .and_then(|boosted| boosted.checked_mul(staking.group_rate(self.group) as u128))
+            .ok_or_else(|| error!(StakingError::MathOverflow))?
+            / (BASE_WEIGHT * GROUP_BASE_RATE as u128);
+
+        u64::try_from(boosted).map_err(|_| error!(StakingError::MathOverflow))
+    }
+
+    /// Returns true while the NFT can't be unstaked.
+    /// Locks are released once the staking ended
+    pub fn is_locked(&self, staking: &Staking, now: i64) -> bool {
+        now < self.locked_until && !staking.has_ended(now)
+    }
+}
+
+/// Commits a freshly staked NFT to the lock tier chosen by the staker, if any
+pub fn lock_stake(
+    staking: &Staking,
+    staked_nft: &mut StakedNft,
+    lock_tier: Option<u8>,
+) -> Result<()> {
+    let tier: LockTier = match lock_tier {
+        Some(index) => *staking.lock_tiers
+            .get(index as usize)
+            .ok_or_else(|| error!(StakingError::InvalidLockTier))?,
+        None => return Ok(()),
+    };
+
+    staked_nft.locked_until = staked_nft.staked_at
+        .checked_add(tier.duration)
+        .ok_or_else(|| error!(StakingError::MathOverflow))?;
+    staked_nft.lock_boost = tier.boost;
+
+    Ok(())
+}
+
+/// Adds a freshly staked NFT to the project reward accounting, and to its reward group
+pub fn register_stake(staking: &mut Staking, staked_nft: &mut StakedNft, now: i64) -> Result<()> {
+    if let Some(index) = staked_nft.group {
+        let group = staking.reward_groups
+            .get_mut(index as usize)
+            .ok_or_else(|| error!(StakingError::InvalidRewardGroups))?;
+        if group.cap != 0 && group.nfts_staked >= group.cap {
+            return err!(StakingError::RewardGroupFull);
+        }
+        group.nfts_staked += 1;
+    }
+
+    staking.accrue(now)?;
+
+    staked_nft.weight = staked_nft.multiplier_at(staking, now)?;
+    staked_nft.reward_debt = staking.pool_share(staked_nft.weight)?;
+    staking.total_weight = staking.total_weight
+        .checked_add(staked_nft.weight)
+        .ok_or_else(|| error!(StakingError::MathOverflow))?;
+
+    Ok(())
+}
+
+/// Registers the weight of a stake created before weights were tracked,
+/// along with the rewards it accrued so far
+pub fn register_migrated_stake(staking: &mut Staking, staked_nft: &mut StakedNft, now: i64) -> Result<()> {
+    // Only per NFT projects existed before weights were tracked
+    if staked_nft.weight != 0 || staking.reward_mode != RewardMode::PerNft {
+        return Ok(());
+    }
+
+    staking.accrue(now)?;
+
+    // Paid with the next claim, the index only covers the stake from now on
+    let pending = staking.emission_between(staked_nft.last_claim, now)?
+        .checked_mul(staked_nft.rarity_multiplier as u128)
+        .ok_or_else(|| error!(StakingError::MathOverflow))?
+        / BASE_WEIGHT
+        / SECONDS_PER_DAY;
+    staked_nft.pending_rewards = u64::try_from(pending)
+        .map_err(|_| error!(StakingError::MathOverflow))?;
+    staking.accrued_liability = staking.accrued_liability.saturating_add(staked_nft.pending_rewards);
+
+    staked_nft.weight = staked_nft.rarity_multiplier;
+    staked_nft.reward_debt = staking.pool_share(staked_nft.weight)?;
+    staking.total_weight = staking.total_weight
+        .checked_add(staked_nft.weight)
+        .ok_or_else(|| error!(StakingError::MathOverflow))?;
+
+    Ok(())
+}
+
+/// Computes the rewards a staked NFT earned since its last claim and marks them as claimed.
+/// The weight only catches up with the loyalty boost here, as the index can't be integrated
+/// per NFT: stakers earn each loyalty step from their next claim on.
+/// NFTs that didn't revalidate after a root change earn nothing past the grace period
+pub fn settle_rewards(staking: &mut Staking, staked_nft: &mut StakedNft, now: i64) -> Result<u64> {
+    let until = if staked_nft.is_validated(staking) || staking.uses_metadata_rules() {
+        now
+    } else {
+        now.min(staking.revalidation_deadline())
+    };
+
+    settle_rewards_until(staking, staked_nft, until, now)
+}
+
+/// Settles a staked NFT at `now` while only paying what it earned up to `until`, the rest is
+/// forfeited. The index doesn't keep its history, so the rewards are split pro rata by time
+fn settle_rewards_until(
+    staking: &mut Staking,
+    staked_nft: &mut StakedNft,
+    until: i64,
+    now: i64,
+) -> Result<u64> {
+    staking.accrue(now)?;
+    let until = until.clamp(staked_nft.last_claim, now);
+
+    let share = staking.pool_share(staked_nft.weight)?;
+    let pending = share.saturating_sub(staked_nft.reward_debt) / PRECISION;
+    staked_nft.reward_debt = share;
+    let rewards_amount = match now - staked_nft.last_claim {
+        0 => pending,
+        elapsed => pending * (until - staked_nft.last_claim) as u128 / elapsed as u128,
+    };
+
+    // Rewards accrued before the stake was migrated
+    let rewards_amount = u64::try_from(rewards_amount)
+        .ok()
+        .and_then(|rewards_amount| rewards_amount.checked_add(staked_nft.pending_rewards))
+        .ok_or_else(|| error!(StakingError::MathOverflow))?;
+    let accrued = u64::try_from(pending)
+        .unwrap_or(u64::MAX)
+        .saturating_add(staked_nft.pending_rewards);
+    staked_nft.pending_rewards = 0;
+
+    staked_nft.last_claim = now;
+    staking.accrued_liability = staking.accrued_liability.saturating_sub(accrued);
+
+    // Register the loyalty earned so far
+    sync_stake_weight(staking, staked_nft, now)?;
+
+    Ok(rewards_amount)
+}
+
+/// Registers the weight of a settled stake for its rarity and the loyalty earned at `now`.
+/// Stakes not migrated yet have no weight to update
+fn sync_stake_weight(staking: &mut Staking, staked_nft: &mut StakedNft, now: i64) -> Result<()> {
+    if staked_nft.weight == 0 {
+        return Ok(());
+    }
+
+    register_weight(staking, staked_nft, now)
+}
+
+/// Replaces the weight of a settled stake in the pool
+fn register_weight(staking: &mut Staking, staked_nft: &mut StakedNft, now: i64) -> Result<()> {
+    let weight = staked_nft.multiplier_at(staking, now)?;
+    staking.total_weight = staking.total_weight
+        .saturating_sub(staked_nft.weight)
+        .checked_add(weight)
+        .ok_or_else(|| error!(StakingError::MathOverflow))?;
+    staked_nft.weight = weight;
+    staked_nft.reward_debt = staking.pool_share(weight)?;
+
+    Ok(())
+}
+
+/// Moves a stake to a new rarity multiplier. Rewards must have been settled first.
+/// Revoked stakes have no rarity and no weight, they get their weight back here
+pub fn set_rarity(staking: &mut Staking, staked_nft: &mut StakedNft, rarity_multiplier: u64, now: i64) -> Result<()> {
+    let registered = staked_nft.weight != 0 || staked_nft.rarity_multiplier == 0;
+    staked_nft.rarity_multiplier = rarity_multiplier;
+    if !registered {
+        return Ok(());
+    }
+
+    register_weight(staking, staked_nft, now)
+}
+
+/// Accrues the rewards a fungible token position earned since its last update into its
+/// pending rewards, then moves its weight to the current token weight
+pub fn settle_position(staking: &mut Staking, position: &mut StakedPosition, now: i64) -> Result<()> {
+    staking.accrue(now)?;
+
+    let earned = staking.pool_share(position.weight)?.saturating_sub(position.reward_debt) / PRECISION;
+
+    position.pending_rewards = u64::try_from(earned)
+        .ok()
+        .and_then(|earned| position.pending_rewards.checked_add(earned))
+        .ok_or_else(|| error!(StakingError::MathOverflow))?;
+    position.last_claim = now;
+
+    sync_position_weight(staking, position)
+}
+
+/// Registers the weight of a position for its current amount, once its rewards are settled
+pub fn sync_position_weight(staking: &mut Staking, position: &mut StakedPosition) -> Result<()> {
+    let weight = staking.token_weight(&position.mint, position.amount, position.decimals)?;
+    staking.total_weight = staking.total_weight
+        .saturating_sub(position.weight)
+        .checked_add(weight)
+        .ok_or_else(|| error!(StakingError::MathOverflow))?;
+    position.weight = weight;
+    position.reward_debt = staking.pool_share(weight)?;
+
+    Ok(())
+}
+
+/// Takes the pending rewards of a settled position out of the project liability
+pub fn take_position_rewards(staking: &mut Staking, position: &mut StakedPosition) -> u64 {
+    let rewards_amount = position.pending_rewards;
+    position.pending_rewards = 0;
+    staking.accrued_liability = staking.accrued_liability.saturating_sub(rewards_amount);
+
+    rewards_amount
+}
+
+/// Removes an unstaked NFT from the project reward accounting, and from its reward group.
+/// Rewards must have been settled first
+pub fn unregister_stake(staking: &mut Staking, staked_nft: &StakedNft) {
+    staking.total_weight = staking.total_weight.saturating_sub(staked_nft.weight);
+    if let Some(group) = staked_nft.group.and_then(|index| staking.reward_groups.get_mut(index as usize)) {
+        group.nfts_staked = group.nfts_staked.saturating_sub(1);
+    }
+}
+
+/// Pays `rewards_amount` to `staker` like `pay_or_owe`, through their owed rewards account
+/// created on first use with `bump`
+pub fn pay_or_owe_staker(
+    staking: &mut Staking,
+    owed_rewards: &mut OwedRewards,
+    bump: u8,
+    staker: Pubkey,
+    rewards_amount: u64,
+    available: u64,
+) -> Result<u64> {
+    owed_rewards.bump = bump;
+    owed_rewards.key = staking.key;
+    owed_rewards.staker = staker;
+
+    pay_or_owe(staking, owed_rewards, rewards_amount, available)
+}
+
+/// Adds `rewards_amount` to what the staker is already owed and returns the part the vault
+/// can pay now, given its `available` balance. The rest stays owed to the staker
+pub fn pay_or_owe(
+    staking: &mut Staking,
+    owed_rewards: &mut OwedRewards,
+    rewards_amount: u64,
+    available: u64,
+) -> Result<u64> {
+    let due = owed_rewards.amount
+        .checked_add(rewards_amount)
+        .ok_or_else(|| error!(StakingError::MathOverflow))?;
+    let amount_paid = due.min(available);
+    let amount_owed = due - amount_paid;
+
+    staking.total_owed = staking.total_owed
+        .saturating_sub(owed_rewards.amount)
+        .checked_add(amount_owed)
+        .ok_or_else(|| error!(StakingError::MathOverflow))?;
+    owed_rewards.amount = amount_owed;
+
+    if amount_owed > 0 {
+        msg!("Not enough funds, rewards owed: {}", amount_owed);
+    }
+
+    Ok(amount_paid)
+}
+
+/// Sends the rewards `forfeited` by an early unstake to the penalty treasury, expected first in
+/// `treasury_accounts`. They stay in the rewards account when the project has no treasury
+#[allow(clippy::too_many_arguments)]
+pub fn route_forfeited<'info>(
+    staking: &Staking,
+    forfeited: u64,
+    treasury_accounts: &[AccountInfo<'info>],
+    token_program: &AccountInfo<'info>,
+    rewards_account: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    if forfeited == 0 {
+        return Ok(());
+    }
+    msg!("Early unstake penalty, rewards forfeited: {}", forfeited);
+
+    let treasury = staking.early_unstake_penalty.treasury;
+    let available = token_interface::amount(rewards_account)?;
+    if treasury == Pubkey::default() || available == 0 {
+        return Ok(());
+    }
+
+    let treasury_account = treasury_accounts
+        .first()
+        .filter(|account| account.key() == treasury)
+        .ok_or_else(|| error!(StakingError::InvalidAccount))?;
+    token_interface::transfer_checked(
+        token_program,
+        rewards_account,
+        mint,
+        treasury_account,
+        escrow,
+        forfeited.min(available),
+        signer,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+//! Administration roles of a project.
+//! The owner holds every permission and grants some of them to other wallets in the role table
+//! of the project, so an automation key can update allowlists without being able to withdraw
+//! the rewards vault. Ownership itself and the role table stay with the owner.
+
+use anchor_lang::prelude::*;
+
+use crate::Staking;
+
+/// Changes the staking parameters, the reward rates and the lock, loyalty and penalty settings
+pub const CONFIG: u8 = 1 << 0;
+
+/// Withdraws rewards from the vault
+pub const TREASURER: u8 = 1 << 1;
+
+/// Changes which NFTs can be staked and their rarity: roots, collection, creator and rarities
+pub const ALLOWLIST: u8 = 1 << 2;
+
+/// Every permission, held by the owner
+pub const ALL: u8 = CONFIG | TREASURER | ALLOWLIST;
+
+impl Staking {
+    /// Returns the permission bits of `key` in the project
+    pub fn permissions_of(&self, key: &Pubkey) -> u8 {
+        if *key == self.owner {
+            return ALL;
+        }
+
+        self.roles
+            .iter()
+            .find(|role| role.key == *key)
+            .map_or(0, |role| role.permissions)
+    }
+
+    /// Returns true if `key` holds every bit of `permission`
+    pub fn can(&self, key: &Pubkey, permission: u8) -> bool {
+        self.permissions_of(key) & permission == permission
+    }
+}
@@ -0,0 +1,200 @@
+//! Minimal interface over the legacy token program and Token-2022, used for the reward token.
+//! anchor-spl 0.26 only knows the legacy program, so reward accounts are unpacked here and
+//! moved with `transfer_checked`, which both programs implement
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use solana_program::program::{invoke, invoke_signed};
+use spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use spl_token_2022::extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions};
+use spl_token_2022::state::{Account, Mint};
+
+use crate::errors::StakingError;
+
+/// Returns true for the legacy token program and Token-2022
+pub fn is_token_program(key: &Pubkey) -> bool {
+    *key == spl_token::ID || *key == spl_token_2022::ID
+}
+
+/// Returns true if `account` is a token account of `mint` owned by `owner`
+pub fn is_account_of(account: &AccountInfo, mint: &Pubkey, owner: &Pubkey) -> bool {
+    if !is_token_program(account.owner) {
+        return false;
+    }
+    let data = account.data.borrow();
+    match StateWithExtensions::<Account>::unpack(&data) {
+        Ok(state) => state.base.mint == *mint && state.base.owner == *owner,
+        Err(_) => false,
+    }
+}
+
+/// Returns the balance of the token `account`
+pub fn amount(account: &AccountInfo) -> Result<u64> {
+    require!(is_token_program(account.owner), StakingError::InvalidAccount);
+    let data = account.data.borrow();
+    let state = StateWithExtensions::<Account>::unpack(&data)?;
+    Ok(state.base.amount)
+}
+
+/// Returns the mint of the token `account`
+pub fn mint(account: &AccountInfo) -> Result<Pubkey> {
+    require!(is_token_program(account.owner), StakingError::InvalidAccount);
+    let data = account.data.borrow();
+    let state = StateWithExtensions::<Account>::unpack(&data)?;
+    Ok(state.base.mint)
+}
+
+/// Returns the decimals of `mint`
+pub fn decimals(mint: &AccountInfo) -> Result<u8> {
+    let data = mint.data.borrow();
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+    Ok(state.base.decimals)
+}
+
+/// Returns the fee withheld by the mint when transferring `amount`, zero without transfer fees
+pub fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let data = mint.data.borrow();
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+    match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or_else(|| error!(StakingError::MathOverflow)),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Transfers `amount` from `from` to `to` with `transfer_checked`, signed by `authority`.
+/// Returns the amount credited to `to`, net of the mint transfer fee
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_checked<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    signer: &[&[&[u8]]],
+) -> Result<u64> {
+    let decimals = decimals(mint)?;
+    let fee = transfer_fee(mint, amount)?;
+
+    let ix = spl_token_2022::instruction::transfer_checked(
+        token_program.key,
+        from.key,
+        mint.key,
+        to.key,
+        authority.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    invoke_signed(
+        &ix,
+        &[
+            from.clone(),
+            mint.clone(),
+            to.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+        signer,
+    )?;
+
+    Ok(amount - fee)
+}
+
+/// Creates the token `account` of `mint` at a PDA signed by `account_signer`, sized for the
+/// extensions the mint requires on its accounts, and sets `authority` as its owner
+#[allow(clippy::too_many_arguments)]
+pub fn create_account<'info>(
+    token_program: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    account_signer: &[&[&[u8]]],
+) -> Result<()> {
+    let space = {
+        let data = mint.data.borrow();
+        let state = StateWithExtensions::<Mint>::unpack(&data)?;
+        let extensions = ExtensionType::get_required_init_account_extensions(
+            &state.get_extension_types()?,
+        );
+        ExtensionType::get_account_len::<Account>(&extensions)
+    };
+
+    create_pda(account, space, token_program.key, payer, system_program, account_signer)?;
+
+    let ix = spl_token_2022::instruction::initialize_account3(
+        token_program.key,
+        account.key,
+        mint.key,
+        authority.key,
+    )?;
+    invoke(&ix, &[account.clone(), mint.clone(), token_program.clone()])?;
+
+    Ok(())
+}
+
+/// Creates `account` at a PDA signed by `account_signer` with `space` bytes owned by `owner`.
+/// Lamports sent to the address beforehand can't block it: the account is topped up to the
+/// rent exemption, then allocated and assigned instead of created
+pub fn create_pda<'info>(
+    account: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    account_signer: &[&[&[u8]]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    if account.lamports() == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+                account_signer,
+            ),
+            rent,
+            space as u64,
+            owner,
+        );
+    }
+
+    if rent > account.lamports() {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            rent - account.lamports(),
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: account.clone(),
+            },
+            account_signer,
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Assign {
+                account_to_assign: account.clone(),
+            },
+            account_signer,
+        ),
+        owner,
+    )
+}
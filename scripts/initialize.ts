@@ -14,6 +14,7 @@ import {
   PublicKey,
   SystemProgram,
   SYSVAR_RENT_PUBKEY,
+  SYSVAR_CLOCK_PUBKEY,
 } from "@solana/web3.js";
 // TODO move these two out of tests
 import { buildLeaves } from "../tests/helpers";
@@ -73,6 +74,7 @@ const initialize = async (network: string) => {
   const totalSupply = new BN(config.totalSupply);
   const dailyRewards = new BN(config.dailyRewards).mul(new BN(10 ** 9));
   const start = new BN(config.start);
+  const end = config.end ? new BN(config.end) : null;
 
   const leaves = buildLeaves(
     mints.map((e, i) => ({
@@ -119,6 +121,8 @@ const initialize = async (network: string) => {
       dailyRewards,
       start,
       tree.getRootArray(),
+      { [config.rewardMode]: {} },
+      end,
       {
         accounts: {
           stakingKey: stakingKey,
@@ -141,11 +145,12 @@ const initialize = async (network: string) => {
       dailyRewards,
       start,
       tree.getRootArray(),
+      end,
       {
         accounts: {
           staking: stakingAddress,
           owner: wallet.payer.publicKey,
-          newOwner: wallet.payer.publicKey,
+          clock: SYSVAR_CLOCK_PUBKEY,
         },
         signers: [wallet.payer],
       }
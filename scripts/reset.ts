@@ -14,6 +14,7 @@ import {
   PublicKey,
   SystemProgram,
   SYSVAR_RENT_PUBKEY,
+  SYSVAR_CLOCK_PUBKEY,
 } from "@solana/web3.js";
 import { buildLeaves } from "../tests/helpers";
 import { MerkleTree } from "../tests/helpers/merkleTree";
@@ -76,6 +77,7 @@ const reset = async (network: string) => {
   const totalSupply = new BN(config.totalSupply);
   const dailyRewards = new BN(config.dailyRewards).mul(new BN(10 ** 9));
   const start = new BN(config.start);
+  const end = config.end ? new BN(config.end) : null;
 
   const leaves = buildLeaves(
     mints.map((e, i) => ({
@@ -114,11 +116,12 @@ const reset = async (network: string) => {
     dailyRewards,
     start,
     tree.getRootArray(),
+    end,
     {
       accounts: {
         staking: stakingAddress,
         owner: wallet.payer.publicKey,
-        newOwner: wallet.payer.publicKey,
+        clock: SYSVAR_CLOCK_PUBKEY,
       },
       signers: [wallet.payer],
     }
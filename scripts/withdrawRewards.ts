@@ -9,7 +9,7 @@ import {
 } from "@project-serum/anchor";
 import { BN } from "@project-serum/anchor";
 import { Token, TOKEN_PROGRAM_ID } from "@solana/spl-token";
-import { PublicKey } from "@solana/web3.js";
+import { PublicKey, SYSVAR_CLOCK_PUBKEY } from "@solana/web3.js";
 
 import key from "../key.json";
 import { Staking, IDL as StakingIDL } from "../target/types/staking";
@@ -73,7 +73,7 @@ const withdrawRewards = async (network: string, amount: string) => {
 
   const withdraw = new BN(amount);
 
-  await stakingProgram.rpc.withdrawRewards(withdraw, {
+  await stakingProgram.rpc.withdrawRewards(withdraw, false, {
         accounts: {
           staking: stakingAddress,
           escrow: staking.escrow,
@@ -82,6 +82,7 @@ const withdrawRewards = async (network: string, amount: string) => {
           owner: wallet.payer.publicKey,
           ownerAccount: ownerAccount.address,
           tokenProgram: TOKEN_PROGRAM_ID,
+          clock: SYSVAR_CLOCK_PUBKEY,
         },
         signers: [wallet.payer],
       });
@@ -0,0 +1,118 @@
+import { Connection, Keypair, PublicKey, Transaction, sendAndConfirmTransaction } from "@solana/web3.js";
+import * as anchor from "@project-serum/anchor";
+import {
+  createCreateTreeInstruction,
+  createMintV1Instruction,
+  computeDataHash,
+  computeCreatorHash,
+  getLeafAssetId,
+  MetadataArgs,
+  TokenProgramVersion,
+  TokenStandard,
+  PROGRAM_ID as BUBBLEGUM_PROGRAM_ID,
+} from "@metaplex-foundation/mpl-bubblegum";
+import {
+  createAllocTreeIx,
+  ConcurrentMerkleTreeAccount,
+  SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+  SPL_NOOP_PROGRAM_ID,
+} from "@solana/spl-account-compression";
+
+// Small tree whose canopy covers all the proof but the sibling leaf
+const MAX_DEPTH = 3;
+const MAX_BUFFER_SIZE = 8;
+const CANOPY_DEPTH = MAX_DEPTH - 1;
+
+export { BUBBLEGUM_PROGRAM_ID, SPL_ACCOUNT_COMPRESSION_PROGRAM_ID, SPL_NOOP_PROGRAM_ID };
+
+export const findTreeAuthority = (merkleTree: PublicKey) =>
+  PublicKey.findProgramAddressSync([merkleTree.toBuffer()], BUBBLEGUM_PROGRAM_ID)[0];
+
+// Fetches the current root of a concurrent merkle tree
+export const getTreeRoot = async (connection: Connection, merkleTree: PublicKey) => {
+  const account = await ConcurrentMerkleTreeAccount.fromAccountAddress(connection, merkleTree);
+  return Array.from(account.getCurrentRoot());
+};
+
+// Proof accounts of a leaf not covered by the canopy. With a single minted leaf, its sibling is empty
+export const getLeafProof = () => [
+  { pubkey: PublicKey.default, isSigner: false, isWritable: false },
+];
+
+// Creates a tree and mints one compressed NFT to the payer, returning what's needed to transfer it
+export async function mintCompressedNft(
+  connection: Connection,
+  payer: Keypair,
+) {
+  const merkleTree = Keypair.generate();
+  const treeAuthority = findTreeAuthority(merkleTree.publicKey);
+
+  const allocTree = await createAllocTreeIx(
+    connection,
+    merkleTree.publicKey,
+    payer.publicKey,
+    { maxDepth: MAX_DEPTH, maxBufferSize: MAX_BUFFER_SIZE },
+    CANOPY_DEPTH
+  );
+  const createTree = createCreateTreeInstruction(
+    {
+      treeAuthority,
+      merkleTree: merkleTree.publicKey,
+      payer: payer.publicKey,
+      treeCreator: payer.publicKey,
+      logWrapper: SPL_NOOP_PROGRAM_ID,
+      compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+    },
+    { maxDepth: MAX_DEPTH, maxBufferSize: MAX_BUFFER_SIZE, public: false }
+  );
+  await sendAndConfirmTransaction(
+    connection,
+    new Transaction().add(allocTree, createTree),
+    [payer, merkleTree]
+  );
+
+  const metadata: MetadataArgs = {
+    name: "Floppy Key",
+    symbol: "cKey",
+    uri: "https://arweave.net/drt4FYPXgD1H7TJIUb34zySyR3ZQQH1x8zsJiMtVJwU",
+    sellerFeeBasisPoints: 500,
+    primarySaleHappened: false,
+    isMutable: false,
+    editionNonce: null,
+    tokenStandard: TokenStandard.NonFungible,
+    collection: null,
+    uses: null,
+    tokenProgramVersion: TokenProgramVersion.Original,
+    creators: [],
+  };
+  const mint = createMintV1Instruction(
+    {
+      treeAuthority,
+      leafOwner: payer.publicKey,
+      leafDelegate: payer.publicKey,
+      merkleTree: merkleTree.publicKey,
+      payer: payer.publicKey,
+      treeDelegate: payer.publicKey,
+      logWrapper: SPL_NOOP_PROGRAM_ID,
+      compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+    },
+    { message: metadata }
+  );
+  await sendAndConfirmTransaction(connection, new Transaction().add(mint), [payer]);
+
+  const nonce = 0;
+  const assetId = await getLeafAssetId(merkleTree.publicKey, new anchor.BN(nonce));
+
+  return {
+    merkleTree: merkleTree.publicKey,
+    treeAuthority,
+    assetId,
+    leaf: {
+      root: await getTreeRoot(connection, merkleTree.publicKey),
+      dataHash: Array.from(computeDataHash(metadata)),
+      creatorHash: Array.from(computeCreatorHash([])),
+      nonce: new anchor.BN(nonce),
+      index: 0,
+    },
+  };
+}
@@ -0,0 +1,43 @@
+import { Connection, Keypair, PublicKey, SystemProgram, Transaction, TransactionInstruction, sendAndConfirmTransaction } from "@solana/web3.js";
+
+export const MPL_CORE_PROGRAM_ID = new PublicKey("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+
+const CREATE_V1 = 0;
+
+const borshString = (value: string) => {
+  const bytes = Buffer.from(value, "utf8");
+  const len = Buffer.alloc(4);
+  len.writeUInt32LE(bytes.length);
+  return Buffer.concat([len, bytes]);
+};
+
+// Optional accounts left out are replaced by the Core program id
+const createV1Instruction = (asset: PublicKey, payer: PublicKey, name: string, uri: string) =>
+  new TransactionInstruction({
+    programId: MPL_CORE_PROGRAM_ID,
+    keys: [
+      { pubkey: asset, isSigner: true, isWritable: true },
+      { pubkey: MPL_CORE_PROGRAM_ID, isSigner: false, isWritable: false }, // collection
+      { pubkey: MPL_CORE_PROGRAM_ID, isSigner: false, isWritable: false }, // authority
+      { pubkey: payer, isSigner: true, isWritable: true },
+      { pubkey: MPL_CORE_PROGRAM_ID, isSigner: false, isWritable: false }, // owner
+      { pubkey: MPL_CORE_PROGRAM_ID, isSigner: false, isWritable: false }, // update authority
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: MPL_CORE_PROGRAM_ID, isSigner: false, isWritable: false }, // log wrapper
+    ],
+    data: Buffer.concat([
+      Buffer.from([CREATE_V1, 0]), // data state: account
+      borshString(name),
+      borshString(uri),
+      Buffer.from([0]), // no plugins
+    ]),
+  });
+
+export const createCoreAsset = async (connection: Connection, payer: Keypair) => {
+  const asset = Keypair.generate();
+  const tx = new Transaction().add(
+    createV1Instruction(asset.publicKey, payer.publicKey, "Core NFT", "https://example.com/core.json")
+  );
+  await sendAndConfirmTransaction(connection, tx, [payer, asset]);
+  return asset.publicKey;
+};
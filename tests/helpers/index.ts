@@ -12,7 +12,7 @@ import {
   createAssociatedTokenAccount,
 } from "@solana/spl-token";
 
-import { PublicKey, Signer } from "@solana/web3.js";
+import { Connection, Keypair, PublicKey, Signer, SystemProgram, Transaction, sendAndConfirmTransaction } from "@solana/web3.js";
 
 import { MerkleTree } from "./merkleTree";
 
@@ -22,6 +22,8 @@ import {
 } from "../helpers/ocpUtils";
 
 import { mintMetaplex, mintProgrammableNft } from "./metaplexUtils"
+import { mintCompressedNft } from "./bubblegumUtils";
+import { createCoreAsset } from "./coreUtils";
 
 export const FEES_LAMPORTS = 10_000_000;
 export const FEES_ACCOUNT: PublicKey = new PublicKey('WHduhbnLJnGNcjBhiGp58kSKMcph6G6Aaq1MiPXj7yd');
@@ -36,6 +38,31 @@ export const findAssociatedAddress = async (
   );
 };
 
+export const findOwedRewardsAddress = async (
+  programId: PublicKey,
+  stakingKey: PublicKey,
+  staker: PublicKey
+) => {
+  const [owedRewards] = await PublicKey.findProgramAddress(
+    [Buffer.from("owed"), stakingKey.toBuffer(), staker.toBuffer()],
+    programId
+  );
+  return owedRewards;
+};
+
+// Sends lamports to an address the program creates an account at, as anyone could beforehand
+export const fundAddress = async (
+  connection: Connection,
+  payer: Keypair,
+  address: PublicKey,
+  lamports: number = 1
+) => {
+  const tx = new Transaction().add(
+    SystemProgram.transfer({ fromPubkey: payer.publicKey, toPubkey: address, lamports })
+  );
+  await sendAndConfirmTransaction(connection, tx, [payer]);
+};
+
 export const assertFail = async (pendingTx: Promise<any>, error?: string) => {
   const log = console.log;
   console.log = () => {};
@@ -218,6 +245,44 @@ export const merkleCollectionMetaplex = async (
   };
 };
 
+//TODO move from here
+export const merkleCollectionCompressed = async (
+  payer: Keypair,
+  provider: anchor.Provider
+) => {
+  // Mint one compressed NFT, identified by its asset id
+  const cnft = await mintCompressedNft(provider.connection, payer);
+  expect(cnft.assetId.toBase58()).to.not.equal(null);
+
+  const leaves = buildLeaves([{ mint: cnft.assetId, rarityMultiplier: 1 }]);
+  const tree = new MerkleTree(leaves);
+  return {
+    cnft,
+    tree,
+  };
+};
+
+export const merkleCollectionCore = async (
+  payer: Keypair,
+  n: number,
+  provider: anchor.Provider
+) => {
+  // Core assets are identified by their asset address
+  const assets: PublicKey[] = [];
+  for (let i = 0; i < n; i++) {
+    assets.push(await createCoreAsset(provider.connection, payer));
+  }
+
+  const leaves = buildLeaves(
+    assets.map((asset, i) => ({ mint: asset, rarityMultiplier: i }))
+  );
+  const tree = new MerkleTree(leaves);
+  return {
+    assets,
+    tree,
+  };
+};
+
 export const buildLeaves = (
   data: { mint: PublicKey; rarityMultiplier: number; }[]
 ) => {
@@ -24,6 +24,11 @@ import { testUnstakeOcp } from "./suites/unstakeOcp";
 import { testStakeMpl } from "./suites/stakeMpl";
 import { testUnstakeMpl } from "./suites/unstakeMpl";
 import { testUnstakeMplCustodial } from "./suites/unstakeMplCustodial";
+import { testStakeFreeze } from "./suites/stakeFreeze";
+import { testStakeCnft } from "./suites/stakeCnft";
+import { testStakeCore } from "./suites/stakeCore";
+import { testToken2022Rewards } from "./suites/token2022Rewards";
+import { testStakeTokens } from "./suites/stakeTokens";
 import { testWithdrawRewards } from "./suites/withdrawRewards";
 import { testMigrateEscrow } from "./suites/migrateEscrow";
 
@@ -75,6 +80,11 @@ describe("Staking Tests Suite", () => {
   //testStakeMpl(state, provider);
   testUnstakeMpl(state, provider);
   //testUnstakeMplCustodial(state, provider); // To make it work, enable stakeNFT instruction
+  //testStakeFreeze(state, provider);
+  //testStakeCnft(state, provider); // Clones Bubblegum and SPL Account Compression, see Anchor.toml
+  //testStakeCore(state, provider); // Clones Metaplex Core, see Anchor.toml
   //testClaimRewards(state, provider);
+  //testToken2022Rewards(state, provider);
+  //testStakeTokens(state, provider);
   //testMigrateEscrow(state, provider);
 });
@@ -14,7 +14,7 @@ import {
   SYSVAR_CLOCK_PUBKEY,
 } from "@solana/web3.js";
 import { Staking } from "../../target/types/staking";
-import { airdropUsers, assertFail, merkleCollection, FEES_LAMPORTS, FEES_ACCOUNT } from "../helpers";
+import { airdropUsers, assertFail, merkleCollection, FEES_LAMPORTS, FEES_ACCOUNT, findOwedRewardsAddress } from "../helpers";
 import { getAccount, createMint, getOrCreateAssociatedTokenAccount, mintTo, transfer, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { MerkleTree } from "../helpers/merkleTree";
 
@@ -123,6 +123,8 @@ export const testClaimRewards = (
         dailyRewards,
         state.start,
         tree.getRootArray(),
+        { perNft: {} },
+        null,
         {
           accounts: {
             stakingKey: stakingKey,
@@ -173,6 +175,8 @@ export const testClaimRewards = (
         bumpsStakedNft,
         tree.getProofArray(indexStaked),
         new BN(indexStaked),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -216,6 +220,8 @@ export const testClaimRewards = (
         bumpsStakedNftOther,
         tree.getProofArray(indexStakedOther),
         new BN(indexStakedOther),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -284,6 +290,7 @@ export const testClaimRewards = (
           escrow: escrow,
           stakedNft: stakedNft,
           staker: holders[indexStaked].publicKey,
+          owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, holders[indexStaked].publicKey),
           mint: mintRewards,
           stakerAccount: stakerAccount.address,
           rewardsAccount: rewardsAccount,
@@ -333,6 +340,139 @@ export const testClaimRewards = (
       );
     });
 
+    it("Owes rewards when the vault is sunset", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          mints[indexStaked].toBuffer(),
+        ],
+        program.programId
+      );
+      const [rewardsAccount] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards", "utf8"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+      const owedRewards = await findOwedRewardsAddress(
+        program.programId,
+        stakingKey,
+        holders[indexStaked].publicKey
+      );
+
+      // Empty the vault
+      const ownerAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        owner,
+        mintRewards,
+        owner.publicKey
+      );
+      const vault = await getAccount(provider.connection, rewardsAccount);
+      // Only a sunset can go below the rewards accrued by the staked NFT
+      await assertFail(
+        program.rpc.withdrawRewards(new BN(vault.amount.toString()), false, {
+          accounts: {
+            staking: stakingAddress,
+            escrow: escrow,
+            mint: mintRewards,
+            rewardsAccount: rewardsAccount,
+            owner: owner.publicKey,
+            ownerAccount: ownerAccount.address,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            clock: SYSVAR_CLOCK_PUBKEY,
+          },
+          signers: [owner],
+        })
+      );
+      await program.rpc.withdrawRewards(new BN(vault.amount.toString()), true, {
+        accounts: {
+          staking: stakingAddress,
+          escrow: escrow,
+          mint: mintRewards,
+          rewardsAccount: rewardsAccount,
+          owner: owner.publicKey,
+          ownerAccount: ownerAccount.address,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          clock: SYSVAR_CLOCK_PUBKEY,
+        },
+        signers: [owner],
+      });
+
+      const stakerAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        holders[indexStaked],
+        mintRewards,
+        holders[indexStaked].publicKey
+      );
+
+      await program.rpc.claimStaking({
+        accounts: {
+          staking: stakingAddress,
+          escrow: escrow,
+          stakedNft: stakedNft,
+          staker: holders[indexStaked].publicKey,
+          owedRewards: owedRewards,
+          mint: mintRewards,
+          stakerAccount: stakerAccount.address,
+          rewardsAccount: rewardsAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          clock: SYSVAR_CLOCK_PUBKEY,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+        },
+        signers: [holders[indexStaked]],
+      });
+
+      // Nothing was paid, everything is owed
+      const owed = await program.account.owedRewards.fetch(owedRewards);
+      const s = await program.account.staking.fetch(stakingAddress);
+      expect(owed.amount.gt(new BN(0))).to.equal(true);
+      expect(s.totalOwed.toString()).to.equal(owed.amount.toString());
+      expect(
+        Number((await getAccount(provider.connection, stakerAccount.address)).amount)
+      ).to.equal(Number(stakerAccount.amount));
+
+      // Top up the vault and get paid what is owed
+      await mintTo(
+        provider.connection,
+        owner,
+        mintRewards,
+        rewardsAccount,
+        owner,
+        10 ** 14
+      );
+
+      await program.rpc.claimOwedRewards({
+        accounts: {
+          staking: stakingAddress,
+          escrow: escrow,
+          owedRewards: owedRewards,
+          staker: holders[indexStaked].publicKey,
+          mint: mintRewards,
+          stakerAccount: stakerAccount.address,
+          rewardsAccount: rewardsAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        signers: [holders[indexStaked]],
+      });
+
+      expect(
+        Number((await getAccount(provider.connection, stakerAccount.address)).amount)
+      ).to.equal(Number(stakerAccount.amount) + Number(owed.amount));
+      const sAfter = await program.account.staking.fetch(stakingAddress);
+      expect(sAfter.totalOwed.toString()).to.equal("0");
+    });
+
     it("Can't claim an unstaked token", async () => {
       const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
         [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
@@ -375,6 +515,7 @@ export const testClaimRewards = (
             escrow: escrow,
             stakedNft: stakedNft,
             staker: holders[indexStaked + 1].publicKey,
+            owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, holders[indexStaked + 1].publicKey),
             mint: mintRewards,
             stakerAccount: stakerAccount.address,
             rewardsAccount: rewardsAccount,
@@ -431,6 +572,7 @@ export const testClaimRewards = (
             escrow: escrow,
             stakedNft: stakedNft,
             staker: holders[indexStaked].publicKey,
+            owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, holders[indexStaked].publicKey),
             mint: mintRewards,
             stakerAccount: stakerAccount.address,
             rewardsAccount: rewardsAccount,
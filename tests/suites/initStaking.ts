@@ -16,6 +16,7 @@ import { Staking } from "../../target/types/staking";
 import {
   airdropUsers,
   assertFail,
+  fundAddress,
   merkleCollection,
   mintAndTransferRewards,
 } from "../helpers";
@@ -87,11 +88,16 @@ export const testInitializeStaking = (
 
       const maximumRarity = new BN(mints.length - 1);
 
+      // Lamports sent to the rewards address beforehand don't block the initialization
+      await fundAddress(provider.connection, state.staker, rewards);
+
       await program.rpc.initializeStaking(
         bumps,
         state.dailyRewards,
         state.start,
         tree.getRootArray(),
+        { perNft: {} },
+        null,
         {
           accounts: {
             stakingKey: state.stakingKey,
@@ -121,6 +127,8 @@ export const testInitializeStaking = (
         tree.getRoot().toJSON().data.toString()
       );
       expect(s.feesExempt).to.equal(false);
+      expect(s.rewardMode).to.deep.equal({ perNft: {} });
+      expect(s.totalWeight.toString()).to.equal("0");
     });
 
   });
@@ -92,6 +92,8 @@ export const testMigrateEscrow = (
         state.dailyRewards,
         state.start,
         tree.getRootArray(),
+        { perNft: {} },
+        null,
         {
           accounts: {
             stakingKey: state.stakingKey,
@@ -16,7 +16,7 @@ import {
   SYSVAR_CLOCK_PUBKEY,
 } from "@solana/web3.js";
 import { Staking } from "../../target/types/staking";
-import { airdropUsers, assertFail, merkleCollection, FEES_LAMPORTS, FEES_ACCOUNT } from "../helpers";
+import { airdropUsers, assertFail, merkleCollection, FEES_LAMPORTS, FEES_ACCOUNT, findOwedRewardsAddress } from "../helpers";
 import { createMint, getOrCreateAssociatedTokenAccount, mintTo, transfer, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { MerkleTree } from "../helpers/merkleTree";
 // Importing local key for tests is far from ideal. TO-DO rethink the strategy
@@ -129,6 +129,8 @@ export const testSetFeesExempt = (
         state.dailyRewards,
         state.start,
         tree.getRootArray(),
+        { perNft: {} },
+        null,
         {
           accounts: {
             stakingKey: stakingKey,
@@ -211,6 +213,8 @@ export const testSetFeesExempt = (
         bumps,
         tree.getProofArray(indexStaked),
         new BN(indexStaked),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -251,18 +255,39 @@ export const testSetFeesExempt = (
 
       // UNSTAKE
 
+      const [rewardsAccount] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards", "utf8"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+      const stakerRewardsAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        holders[indexStaked],
+        mintRewards,
+        holders[indexStaked].publicKey
+      );
+
       await program.rpc.unstakeNft({
         accounts: {
           staking: stakingAddress,
           escrow: escrow,
+          rewardsAccount: rewardsAccount,
           stakedNft: stakedNft,
           staker: holders[indexStaked].publicKey,
+          owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, holders[indexStaked].publicKey),
           mint: mints[indexStaked],
           stakerAccount: accounts[indexStaked],
+          rewardsMint: mintRewards,
+          stakerRewardsAccount: stakerRewardsAccount.address,
           depositAccount: deposit,
           feeReceiverAccount: FEES_ACCOUNT,
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
+          rewardsTokenProgram: TOKEN_PROGRAM_ID,
+          clock: SYSVAR_CLOCK_PUBKEY,
         },
         signers: [holders[indexStaked]],
       });
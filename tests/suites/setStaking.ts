@@ -11,6 +11,7 @@ import {
   Keypair,
   SystemProgram,
   SYSVAR_RENT_PUBKEY,
+  SYSVAR_CLOCK_PUBKEY,
 } from "@solana/web3.js";
 import { Staking } from "../../target/types/staking";
 import { airdropUsers, assertFail, merkleCollection } from "../helpers";
@@ -51,7 +52,6 @@ export const testSetStaking = (
     });
 
     it("Reset the Staking", async () => {
-      const newOwner = Keypair.generate();      
       const newDailyRewards = new BN(100000);
 
       const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
@@ -63,11 +63,12 @@ export const testSetStaking = (
         newDailyRewards,
         state.start,
         tree.getRootArray(),
+        null,
         {
           accounts: {
             staking: stakingAddress,
             owner: state.owner.publicKey,
-            newOwner: newOwner.publicKey,
+            clock: SYSVAR_CLOCK_PUBKEY,
           },
           signers: [state.owner],
         }
@@ -75,8 +76,10 @@ export const testSetStaking = (
 
       const s = await program.account.staking.fetch(stakingAddress);
 
-      expect(s.owner.toString()).to.equal(newOwner.publicKey.toString());
+      expect(s.owner.toString()).to.equal(state.owner.publicKey.toString());
       expect(s.dailyRewards.toString()).to.equal(newDailyRewards.toString());
+      // Rewards were accrued at the previous rate up to the change
+      expect(s.lastUpdate.toNumber()).to.be.at.least(state.start.toNumber());
       expect(s.root.toString()).to.equal(
         tree.getRoot().toJSON().data.toString()
       );
@@ -85,13 +88,588 @@ export const testSetStaking = (
         state.dailyRewards,
         state.start,
         tree.getRootArray(),
+        null,
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+            clock: SYSVAR_CLOCK_PUBKEY,
+          },
+          signers: [state.owner],
+        }
+      );
+    });
+
+    it("Updates a single parameter", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking"), state.stakingKey.toBuffer()],
+        program.programId
+      );
+      const before = await program.account.staking.fetch(stakingAddress);
+
+      // Only the root changes
+      await program.rpc.updateStaking(
+        { dailyRewards: null, start: null, root: tree.getRootArray(), end: null },
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+            clock: SYSVAR_CLOCK_PUBKEY,
+          },
+          signers: [state.owner],
+        }
+      );
+
+      const s = await program.account.staking.fetch(stakingAddress);
+      expect(s.root.toString()).to.equal(
+        tree.getRoot().toJSON().data.toString()
+      );
+      expect(s.start.toString()).to.equal(before.start.toString());
+      expect(s.dailyRewards.toString()).to.equal(before.dailyRewards.toString());
+      expect(String(s.end)).to.equal(String(before.end));
+    });
+
+    it("Delegates the allowlist with a role", async () => {
+      const editor = Keypair.generate();
+      const ALLOWLIST = 4;
+
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking"), state.stakingKey.toBuffer()],
+        program.programId
+      );
+
+      const setRole = (permissions: number) =>
+        program.rpc.setRole(editor.publicKey, permissions, {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        });
+      const update = (update: object) =>
+        program.rpc.updateStaking(
+          { dailyRewards: null, start: null, root: null, end: null, ...update },
+          {
+            accounts: {
+              staking: stakingAddress,
+              owner: editor.publicKey,
+              clock: SYSVAR_CLOCK_PUBKEY,
+            },
+            signers: [editor],
+          }
+        );
+
+      // No permission yet
+      await assertFail(update({ root: tree.getRootArray() }));
+
+      await setRole(ALLOWLIST);
+      let s = await program.account.staking.fetch(stakingAddress);
+      expect(s.roles.length).to.equal(1);
+      expect(s.roles[0].permissions).to.equal(ALLOWLIST);
+
+      // The editor can change the root, but not the config
+      await update({ root: tree.getRootArray() });
+      await assertFail(update({ dailyRewards: new BN(1) }));
+      await assertFail(
+        program.rpc.setLoyalty(
+          { stepDuration: new BN(0), stepBoost: new BN(0), maxBoost: new BN(0) },
+          {
+            accounts: {
+              staking: stakingAddress,
+              owner: editor.publicKey,
+            },
+            signers: [editor],
+          }
+        )
+      );
+
+      // Only the owner manages roles
+      await assertFail(
+        program.rpc.setRole(editor.publicKey, 7, {
+          accounts: {
+            staking: stakingAddress,
+            owner: editor.publicKey,
+          },
+          signers: [editor],
+        })
+      );
+
+      // The config permission alone can't change the root through set_staking
+      await setRole(1);
+      s = await program.account.staking.fetch(stakingAddress);
+      const set = (root: number[]) =>
+        program.rpc.setStaking(s.dailyRewards, s.start, root, s.end, {
+          accounts: {
+            staking: stakingAddress,
+            owner: editor.publicKey,
+            clock: SYSVAR_CLOCK_PUBKEY,
+          },
+          signers: [editor],
+        });
+      await assertFail(set(Array(32).fill(2)));
+      await set(s.root);
+
+      // Nor add a reward group, which is an allowlist change
+      await assertFail(
+        program.rpc.setRewardGroups(
+          [
+            {
+              collection: PublicKey.default,
+              rootIndex: 0,
+              rate: new BN(200),
+              cap: new BN(0),
+              nftsStaked: new BN(0),
+            },
+          ],
+          {
+            accounts: {
+              staking: stakingAddress,
+              owner: editor.publicKey,
+            },
+            signers: [editor],
+          }
+        )
+      );
+
+      await setRole(0);
+      s = await program.account.staking.fetch(stakingAddress);
+      expect(s.roles.length).to.equal(0);
+      await assertFail(update({ root: tree.getRootArray() }));
+    });
+
+    it("Changes the root through an approved proposal", async () => {
+      const approvers = [Keypair.generate(), Keypair.generate()];
+      await airdropUsers(approvers, provider);
+      const newRoot = Array(32).fill(1);
+
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking"), state.stakingKey.toBuffer()],
+        program.programId
+      );
+
+      const propose = async (action: object) => {
+        const s = await program.account.staking.fetch(stakingAddress);
+        const [proposal, proposalBump] = await PublicKey.findProgramAddress(
+          [
+            Buffer.from("proposal"),
+            state.stakingKey.toBuffer(),
+            s.proposalCount.toArrayLike(Buffer, "le", 8),
+          ],
+          program.programId
+        );
+        await program.rpc.createProposal(action, {
+          accounts: {
+            staking: stakingAddress,
+            proposal: proposal,
+            proposer: approvers[0].publicKey,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [approvers[0]],
+        });
+        return proposal;
+      };
+      const approve = (proposal: PublicKey) =>
+        program.rpc.approveProposal({
+          accounts: {
+            staking: stakingAddress,
+            proposal: proposal,
+            approver: approvers[1].publicKey,
+          },
+          signers: [approvers[1]],
+        });
+      const execute = (proposal: PublicKey) =>
+        program.rpc.executeProposal({
+          accounts: {
+            staking: stakingAddress,
+            proposal: proposal,
+            proposer: approvers[0].publicKey,
+            clock: SYSVAR_CLOCK_PUBKEY,
+          },
+        });
+
+      await program.rpc.setApprovers(
+        approvers.map((approver) => approver.publicKey),
+        2,
+        new BN(0),
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        }
+      );
+
+      // The owner alone can no longer change the root
+      await assertFail(
+        program.rpc.updateStaking(
+          { dailyRewards: null, start: null, root: newRoot, end: null },
+          {
+            accounts: {
+              staking: stakingAddress,
+              owner: state.owner.publicKey,
+              clock: SYSVAR_CLOCK_PUBKEY,
+            },
+            signers: [state.owner],
+          }
+        )
+      );
+
+      const proposal = await propose({
+        updateStaking: {
+          update: { dailyRewards: null, start: null, root: newRoot, end: null },
+        },
+      });
+
+      // One approval out of two
+      await assertFail(execute(proposal));
+
+      await approve(proposal);
+      await execute(proposal);
+      let s = await program.account.staking.fetch(stakingAddress);
+      expect(s.root.toString()).to.equal(newRoot.toString());
+
+      // The metadata rules go through a proposal as well
+      await assertFail(
+        program.rpc.setCollection(PublicKey.default, new BN(0), {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        })
+      );
+      const collection = await propose({
+        setCollection: { collection: PublicKey.default, collectionRarity: new BN(0) },
+      });
+      await approve(collection);
+      await execute(collection);
+
+      // And so does the rarity authority
+      await assertFail(
+        program.rpc.setRarityAuthority(approvers[0].publicKey, {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        })
+      );
+      const rarityAuthority = await propose({
+        setRarityAuthority: { rarityAuthority: PublicKey.default },
+      });
+      await approve(rarityAuthority);
+      await execute(rarityAuthority);
+
+      // Turn approvals off for the other tests
+      const disable = await propose({
+        setApprovers: { approvers: [], approvalThreshold: 0, approvalLimit: new BN(0) },
+      });
+      await approve(disable);
+      await execute(disable);
+      s = await program.account.staking.fetch(stakingAddress);
+      expect(s.approvalThreshold).to.equal(0);
+    });
+
+    it("Transfers the ownership in two steps", async () => {
+      const newOwner = Keypair.generate();
+      const stranger = Keypair.generate();
+
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking"), state.stakingKey.toBuffer()],
+        program.programId
+      );
+
+      const proposeOwner = (owner: Keypair, pendingOwner: PublicKey) =>
+        program.rpc.proposeOwner(pendingOwner, {
+          accounts: {
+            staking: stakingAddress,
+            owner: owner.publicKey,
+          },
+          signers: [owner],
+        });
+      const acceptOwner = (pendingOwner: Keypair) =>
+        program.rpc.acceptOwner({
+          accounts: {
+            staking: stakingAddress,
+            pendingOwner: pendingOwner.publicKey,
+          },
+          signers: [pendingOwner],
+        });
+
+      await proposeOwner(state.owner, newOwner.publicKey);
+
+      // Only the proposed owner can accept, and the owner is unchanged until then
+      await assertFail(acceptOwner(stranger));
+      let s = await program.account.staking.fetch(stakingAddress);
+      expect(s.owner.toString()).to.equal(state.owner.publicKey.toString());
+      expect(s.pendingOwner.toString()).to.equal(newOwner.publicKey.toString());
+
+      await acceptOwner(newOwner);
+      s = await program.account.staking.fetch(stakingAddress);
+      expect(s.owner.toString()).to.equal(newOwner.publicKey.toString());
+      expect(s.pendingOwner.toString()).to.equal(PublicKey.default.toString());
+
+      // The previous owner lost its rights
+      await assertFail(proposeOwner(state.owner, state.owner.publicKey));
+
+      await proposeOwner(newOwner, state.owner.publicKey);
+      await acceptOwner(state.owner);
+    });
+
+    it("Schedules more campaign windows than fit at once, one after another", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking"), state.stakingKey.toBuffer()],
+        program.programId
+      );
+      const setWindows = (windows: { start: BN; end: BN }[]) =>
+        program.rpc.setStakingWindows(
+          windows,
+          {
+            accounts: {
+              staking: stakingAddress,
+              owner: state.owner.publicKey,
+              clock: SYSVAR_CLOCK_PUBKEY,
+            },
+            signers: [state.owner],
+          }
+        );
+      const windowsFrom = (from: number, count: number) =>
+        Array(count)
+          .fill(0)
+          .map((_, i) => ({ start: new BN(from + i), end: new BN(from + i + 1) }));
+
+      // At most 8 windows at once
+      const now = Math.round(Date.now() / 1000);
+      await assertFail(setWindows(windowsFrom(now, 9)));
+
+      await setWindows(windowsFrom(now, 8));
+      await new Promise((resolve) => setTimeout(resolve, 9000));
+
+      // Elapsed windows can be dropped to make room for the next ones
+      const later = Math.round(Date.now() / 1000) + 60;
+      await setWindows(windowsFrom(later, 8));
+      const s = await program.account.staking.fetch(stakingAddress);
+      expect(s.windows.length).to.equal(8);
+      expect(s.windows[0].start.toNumber()).to.equal(later);
+
+      await setWindows([]);
+    });
+
+    it("Sets the loyalty curve", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking"), state.stakingKey.toBuffer()],
+        program.programId
+      );
+
+      // Too many steps before reaching the cap
+      await assertFail(
+        program.rpc.setLoyalty(
+          { stepDuration: new BN(30 * 86400), stepBoost: new BN(1), maxBoost: new BN(100) },
+          {
+            accounts: {
+              staking: stakingAddress,
+              owner: state.owner.publicKey,
+            },
+            signers: [state.owner],
+          }
+        )
+      );
+
+      await program.rpc.setLoyalty(
+        { stepDuration: new BN(30 * 86400), stepBoost: new BN(10), maxBoost: new BN(50) },
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        }
+      );
+
+      const s = await program.account.staking.fetch(stakingAddress);
+      expect(s.loyalty.stepDuration.toNumber()).to.equal(30 * 86400);
+      expect(s.loyalty.stepBoost.toNumber()).to.equal(10);
+      expect(s.loyalty.maxBoost.toNumber()).to.equal(50);
+      // Rewards were accrued under the previous curve first
+      expect(s.lastUpdate.toNumber()).to.be.at.least(state.start.toNumber());
+
+      // Disable it again
+      await program.rpc.setLoyalty(
+        { stepDuration: new BN(0), stepBoost: new BN(0), maxBoost: new BN(0) },
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        }
+      );
+    });
+
+    it("Sets the early unstake penalty", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking"), state.stakingKey.toBuffer()],
+        program.programId
+      );
+
+      // More than the pending rewards
+      await assertFail(
+        program.rpc.setEarlyUnstakePenalty(
+          { minDuration: new BN(7 * 86400), rate: new BN(101), treasury: PublicKey.default },
+          {
+            accounts: {
+              staking: stakingAddress,
+              owner: state.owner.publicKey,
+            },
+            signers: [state.owner],
+          }
+        )
+      );
+
+      // A treasury must be passed along
+      await assertFail(
+        program.rpc.setEarlyUnstakePenalty(
+          { minDuration: new BN(7 * 86400), rate: new BN(25), treasury: Keypair.generate().publicKey },
+          {
+            accounts: {
+              staking: stakingAddress,
+              owner: state.owner.publicKey,
+            },
+            signers: [state.owner],
+          }
+        )
+      );
+
+      await program.rpc.setEarlyUnstakePenalty(
+        { minDuration: new BN(7 * 86400), rate: new BN(25), treasury: PublicKey.default },
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        }
+      );
+
+      const s = await program.account.staking.fetch(stakingAddress);
+      expect(s.earlyUnstakePenalty.minDuration.toNumber()).to.equal(7 * 86400);
+      expect(s.earlyUnstakePenalty.rate.toNumber()).to.equal(25);
+      expect(s.earlyUnstakePenalty.treasury.toString()).to.equal(PublicKey.default.toString());
+
+      // Disable it again
+      await program.rpc.setEarlyUnstakePenalty(
+        { minDuration: new BN(0), rate: new BN(0), treasury: PublicKey.default },
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        }
+      );
+    });
+
+    it("Sets the verified collection", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking"), state.stakingKey.toBuffer()],
+        program.programId
+      );
+      const collection = Keypair.generate().publicKey;
+
+      // Collection NFTs need a rarity multiplier
+      await assertFail(
+        program.rpc.setCollection(
+          collection,
+          new BN(0),
+          {
+            accounts: {
+              staking: stakingAddress,
+              owner: state.owner.publicKey,
+            },
+            signers: [state.owner],
+          }
+        )
+      );
+
+      await program.rpc.setCollection(
+        collection,
+        new BN(3),
         {
           accounts: {
             staking: stakingAddress,
-            owner: newOwner.publicKey,
-            newOwner: state.owner.publicKey,
+            owner: state.owner.publicKey,
           },
-          signers: [newOwner],
+          signers: [state.owner],
+        }
+      );
+
+      const s = await program.account.staking.fetch(stakingAddress);
+      expect(s.collection.toString()).to.equal(collection.toString());
+      expect(s.collectionRarity.toNumber()).to.equal(3);
+
+      // Back to the merkle allowlist
+      await program.rpc.setCollection(
+        PublicKey.default,
+        new BN(0),
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        }
+      );
+    });
+
+    it("Sets the verified creator", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking"), state.stakingKey.toBuffer()],
+        program.programId
+      );
+      const creator = Keypair.generate().publicKey;
+
+      // Creator NFTs need a rarity multiplier
+      await assertFail(
+        program.rpc.setCreator(
+          creator,
+          new BN(0),
+          {
+            accounts: {
+              staking: stakingAddress,
+              owner: state.owner.publicKey,
+            },
+            signers: [state.owner],
+          }
+        )
+      );
+
+      await program.rpc.setCreator(
+        creator,
+        new BN(3),
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        }
+      );
+
+      const s = await program.account.staking.fetch(stakingAddress);
+      expect(s.creator.toString()).to.equal(creator.toString());
+      expect(s.creatorRarity.toNumber()).to.equal(3);
+
+      // Disable the rule
+      await program.rpc.setCreator(
+        PublicKey.default,
+        new BN(0),
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
         }
       );
     });
@@ -113,11 +691,12 @@ export const testSetStaking = (
           newDailyRewards,
           state.start,
           tree.getRootArray(),
+          null,
           {
             accounts: {
               staking: stakingAddress,
               owner: newOwner.publicKey,
-              newOwner: newOwner.publicKey,
+              clock: SYSVAR_CLOCK_PUBKEY,
             },
             signers: [newOwner],
           }
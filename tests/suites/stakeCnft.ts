@@ -0,0 +1,326 @@
+import { expect } from "chai";
+import {
+  setProvider,
+  Provider,
+  Program,
+  workspace,
+  BN,
+} from "@project-serum/anchor";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  SYSVAR_CLOCK_PUBKEY,
+} from "@solana/web3.js";
+import { Staking } from "../../target/types/staking";
+import { airdropUsers, assertFail, FEES_LAMPORTS, FEES_ACCOUNT, merkleCollectionCompressed, findOwedRewardsAddress } from "../helpers";
+import { getAccount, createMint, getOrCreateAssociatedTokenAccount, mintTo, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { MerkleTree } from "../helpers/merkleTree";
+import {
+  getLeafProof,
+  getTreeRoot,
+  BUBBLEGUM_PROGRAM_ID,
+  SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+  SPL_NOOP_PROGRAM_ID,
+} from "../helpers/bubblegumUtils";
+
+export const testStakeCnft = (
+  state: {
+    owner: Keypair;
+    staker: Keypair;
+    stakingKey: PublicKey;
+    mintRewards: Token;
+    dailyRewards: BN;
+    start: BN;
+  },
+  provider: Provider
+) =>
+  describe("Stake a compressed NFT", () => {
+    setProvider(provider);
+
+    const program = workspace.Staking as Program<Staking>;
+
+    const dailyRewards = new BN(604800000000);
+
+    let mintRewards: Token, cnft: any;
+
+    let tree: MerkleTree;
+
+    let stakingKey: PublicKey, owner: Keypair, stranger: Keypair;
+
+    const rarityMultiplier = new BN(1);
+
+    beforeEach(async () => {
+      stakingKey = Keypair.generate().publicKey;
+      owner = Keypair.generate();
+      stranger = Keypair.generate();
+
+      await airdropUsers([owner, stranger], provider);
+      mintRewards = await createMint(
+        provider.connection,
+        owner,
+        owner.publicKey,
+        null,
+        9
+      );
+      const nfts = await merkleCollectionCompressed(owner, provider);
+      cnft = nfts.cnft;
+      tree = nfts.tree;
+
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [rewards, rewardsBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+
+      const bumps = {
+        staking: stakingBump,
+        escrow: escrowBump,
+        rewards: rewardsBump,
+      };
+
+      await program.rpc.initializeStaking(
+        bumps,
+        dailyRewards,
+        state.start,
+        tree.getRootArray(),
+        { perNft: {} },
+        null,
+        {
+          accounts: {
+            stakingKey: stakingKey,
+            staking: stakingAddress,
+            escrow: escrow,
+            mint: mintRewards,
+            rewardsAccount: rewards,
+            owner: state.owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [state.owner],
+        }
+      );
+
+      // Mint reward tokens to the staking
+      await mintTo(
+          provider.connection,
+          owner,
+          mintRewards,
+          rewards,
+          owner,
+          10 ** 14
+      );
+
+      // Stake the compressed NFT
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          cnft.assetId.toBuffer(),
+        ],
+        program.programId
+      );
+
+      await program.rpc.stakeCnft(
+        { stakedNft: stakedNftBump, deposit: 0 },
+        tree.getProofArray(0),
+        rarityMultiplier,
+        null,
+        cnft.leaf,
+        null,
+        {
+          accounts: {
+            staking: stakingAddress,
+            escrow: escrow,
+            stakedNft: stakedNft,
+            staker: owner.publicKey,
+            leafDelegate: owner.publicKey,
+            treeAuthority: cnft.treeAuthority,
+            merkleTree: cnft.merkleTree,
+            feeReceiverAccount: FEES_ACCOUNT,
+            clock: SYSVAR_CLOCK_PUBKEY,
+            logWrapper: SPL_NOOP_PROGRAM_ID,
+            compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+            bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          },
+          remainingAccounts: getLeafProof(),
+          signers: [owner],
+        }
+      );
+    });
+
+    it("Stake a compressed token", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          cnft.assetId.toBuffer(),
+        ],
+        program.programId
+      );
+
+      const a = await program.account.stakedNft.fetch(stakedNft);
+      const j = await program.account.staking.fetch(stakingAddress);
+
+      expect(j.nftsStaked.toString()).to.equal(new BN(1).toString());
+      expect(a.staker.toString()).to.equal(owner.publicKey.toString());
+      // Compressed NFTs are identified by their asset id
+      expect(a.mint.toString()).to.equal(cnft.assetId.toString());
+      expect(a.rarityMultiplier.toString()).to.equal(rarityMultiplier.toString());
+    });
+
+    it("Unstake a compressed token", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          cnft.assetId.toBuffer(),
+        ],
+        program.programId
+      );
+      const [rewardsAccount] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards", "utf8"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+
+      const stakerRewardsAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        owner,
+        mintRewards,
+        owner.publicKey
+      );
+
+      const feesBalanceBefore = await provider.connection.getBalance(FEES_ACCOUNT);
+
+      // The leaf is now owned by the escrow, in a tree with a new root
+      const leaf = { ...cnft.leaf, root: await getTreeRoot(provider.connection, cnft.merkleTree) };
+
+      await program.rpc.unstakeCnft(
+        leaf,
+        {
+          accounts: {
+            staking: stakingAddress,
+            escrow: escrow,
+            rewardsAccount: rewardsAccount,
+            stakedNft: stakedNft,
+            staker: owner.publicKey,
+            rewardsMint: mintRewards,
+            stakerRewardsAccount: stakerRewardsAccount.address,
+            owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, owner.publicKey),
+            treeAuthority: cnft.treeAuthority,
+            merkleTree: cnft.merkleTree,
+            feeReceiverAccount: FEES_ACCOUNT,
+            rewardsTokenProgram: TOKEN_PROGRAM_ID,
+            clock: SYSVAR_CLOCK_PUBKEY,
+            logWrapper: SPL_NOOP_PROGRAM_ID,
+            compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+            bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          },
+          remainingAccounts: getLeafProof(),
+          signers: [owner],
+        }
+      );
+
+      const feesBalanceAfter = await provider.connection.getBalance(FEES_ACCOUNT);
+      expect(feesBalanceAfter - feesBalanceBefore).to.equal(FEES_LAMPORTS);
+
+      const j = await program.account.staking.fetch(stakingAddress);
+      expect(j.nftsStaked.toString()).to.equal(new BN(0).toString());
+
+      const stakerRewardsAfter = await getAccount(provider.connection, stakerRewardsAccount.address);
+      expect(Number(stakerRewardsAfter.amount)).to.be.greaterThan(Number(stakerRewardsAccount.amount));
+    });
+
+    it("Can't unstake an unowned compressed token", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          cnft.assetId.toBuffer(),
+        ],
+        program.programId
+      );
+      const [rewardsAccount] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards", "utf8"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+
+      const strangerRewardsAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        stranger,
+        mintRewards,
+        stranger.publicKey
+      );
+
+      const leaf = { ...cnft.leaf, root: await getTreeRoot(provider.connection, cnft.merkleTree) };
+
+      await assertFail(
+        program.rpc.unstakeCnft(
+          leaf,
+          {
+            accounts: {
+              staking: stakingAddress,
+              escrow: escrow,
+              rewardsAccount: rewardsAccount,
+              stakedNft: stakedNft,
+              staker: stranger.publicKey,
+              rewardsMint: mintRewards,
+              stakerRewardsAccount: strangerRewardsAccount.address,
+              owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, stranger.publicKey),
+              treeAuthority: cnft.treeAuthority,
+              merkleTree: cnft.merkleTree,
+              feeReceiverAccount: FEES_ACCOUNT,
+              rewardsTokenProgram: TOKEN_PROGRAM_ID,
+              clock: SYSVAR_CLOCK_PUBKEY,
+              logWrapper: SPL_NOOP_PROGRAM_ID,
+              compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+              bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+              systemProgram: SystemProgram.programId,
+            },
+            remainingAccounts: getLeafProof(),
+            signers: [stranger],
+          }
+        )
+      );
+    });
+
+  });
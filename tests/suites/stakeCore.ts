@@ -0,0 +1,326 @@
+import { expect } from "chai";
+import {
+  setProvider,
+  Provider,
+  Program,
+  workspace,
+  BN,
+} from "@project-serum/anchor";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  SYSVAR_CLOCK_PUBKEY,
+} from "@solana/web3.js";
+import { Staking } from "../../target/types/staking";
+import { airdropUsers, assertFail, FEES_LAMPORTS, FEES_ACCOUNT, merkleCollectionCore, findOwedRewardsAddress } from "../helpers";
+import { MPL_CORE_PROGRAM_ID } from "../helpers/coreUtils";
+import { getAccount, createMint, getOrCreateAssociatedTokenAccount, mintTo, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { MerkleTree } from "../helpers/merkleTree";
+
+const NFTIndex = 1;
+
+export const testStakeCore = (
+  state: {
+    owner: Keypair;
+    staker: Keypair;
+    stakingKey: PublicKey;
+    mintRewards: Token;
+    dailyRewards: BN;
+    start: BN;
+  },
+  provider: Provider
+) =>
+  describe("Stake Metaplex Core assets", () => {
+    setProvider(provider);
+
+    const program = workspace.Staking as Program<Staking>;
+
+    const dailyRewards = new BN(604800000000);
+
+    const n = 10;
+    let mintRewards: Token,
+      assets: PublicKey[],
+      holders: Keypair[];
+
+    let tree: MerkleTree;
+
+    let stakingKey: PublicKey, owner: Keypair, stranger: Keypair;
+
+    const startingAmount = new BN(10 ** 10);
+
+    beforeEach(async () => {
+      stakingKey = Keypair.generate().publicKey;
+      owner = Keypair.generate();
+      stranger = Keypair.generate();
+
+      await airdropUsers([owner, stranger], provider);
+      mintRewards = await createMint(
+        provider.connection,
+        owner,
+        owner.publicKey,
+        null,
+        9
+      );
+      const nfts = await merkleCollectionCore(owner, 2, provider);
+      assets = nfts.assets;
+      tree = nfts.tree;
+
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [rewards, rewardsBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+
+      const bumps = {
+        staking: stakingBump,
+        escrow: escrowBump,
+        rewards: rewardsBump,
+      };
+
+      await program.rpc.initializeStaking(
+        bumps,
+        dailyRewards,
+        state.start,
+        tree.getRootArray(),
+        { perNft: {} },
+        null,
+        {
+          accounts: {
+            stakingKey: stakingKey,
+            staking: stakingAddress,
+            escrow: escrow,
+            mint: mintRewards,
+            rewardsAccount: rewards,
+            owner: state.owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [state.owner],
+        }
+      );
+
+      // Mint reward tokens to the staking
+      await mintTo(
+          provider.connection,
+          owner,
+          mintRewards,
+          rewards,
+          owner,
+          10 ** 14
+      );
+
+    });
+
+    it("Stake and unstake a NFT token", async () => {
+
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          assets[NFTIndex].toBuffer(),
+        ],
+        program.programId
+      );
+
+      const bumps = {
+        stakedNft: stakedNftBump,
+        deposit: 0,
+      };
+
+      await program.rpc.stakeCore(
+        bumps,
+        tree.getProofArray(NFTIndex),
+        new BN(NFTIndex),
+        null,
+        null,
+        {
+          accounts: {
+            staking: stakingAddress,
+            stakedNft: stakedNft,
+            staker: owner.publicKey,
+            asset: assets[NFTIndex],
+            collection: MPL_CORE_PROGRAM_ID,
+            feeReceiverAccount: FEES_ACCOUNT,
+            clock: SYSVAR_CLOCK_PUBKEY,
+            systemProgram: SystemProgram.programId,
+            coreProgram: MPL_CORE_PROGRAM_ID,
+          },
+          signers: [owner],
+        }
+      );
+
+      // The asset stays in the staker wallet, identified by its address
+      const staked = await program.account.stakedNft.fetch(stakedNft);
+      expect(staked.mint.toString()).to.equal(assets[NFTIndex].toString());
+      expect(staked.staker.toString()).to.equal(owner.publicKey.toString());
+
+      await new Promise(resolve => setTimeout(resolve, 1000));
+
+      // Unstake
+
+      const nftsStakedBefore = (
+        await program.account.staking.fetch(stakingAddress)
+      ).nftsStaked;
+
+      const [rewardsAccount] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards", "utf8"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+
+      const stakerRewardsAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        owner,
+        mintRewards,
+        owner.publicKey
+      );
+
+      const rewardsBefore = (await getAccount(provider.connection, rewardsAccount))
+        .amount;
+
+      await program.rpc.unstakeCore(
+        {
+          accounts: {
+            staking: stakingAddress,
+            escrow: escrow,
+            rewardsAccount: rewardsAccount,
+            stakedNft: stakedNft,
+            staker: owner.publicKey,
+            owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, owner.publicKey),
+            asset: assets[NFTIndex],
+            collection: MPL_CORE_PROGRAM_ID,
+            rewardsMint: mintRewards,
+            stakerRewardsAccount: stakerRewardsAccount.address,
+            feeReceiverAccount: FEES_ACCOUNT,
+            rewardsTokenProgram: TOKEN_PROGRAM_ID,
+            clock: SYSVAR_CLOCK_PUBKEY,
+            systemProgram: SystemProgram.programId,
+            coreProgram: MPL_CORE_PROGRAM_ID,
+          },
+          signers: [owner],
+        }
+      );
+
+      // The rewards have been transferred to the staker
+      const stakerRewardsAfter = await getAccount(provider.connection, stakerRewardsAccount.address);
+      const rewardsAfter = (await getAccount(provider.connection, rewardsAccount))
+        .amount;
+      expect(Number(stakerRewardsAfter.amount)).to.equal(
+        Number(stakerRewardsAccount.amount) + Number(rewardsBefore) - Number(rewardsAfter)
+      );
+
+      const j = await program.account.staking.fetch(stakingAddress);
+      expect(j.nftsStaked.toString()).to.equal(
+        nftsStakedBefore.sub(new BN(1)).toString()
+      );
+
+      expect(await provider.connection.getAccountInfo(stakedNft)).to.equal(null);
+    });
+
+    it("Can't unstake an unowned token", async () => {
+
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          assets[NFTIndex].toBuffer(),
+        ],
+        program.programId
+      );
+
+      await program.rpc.stakeCore(
+        { stakedNft: stakedNftBump, deposit: 0 },
+        tree.getProofArray(NFTIndex),
+        new BN(NFTIndex),
+        null,
+        null,
+        {
+          accounts: {
+            staking: stakingAddress,
+            stakedNft: stakedNft,
+            staker: owner.publicKey,
+            asset: assets[NFTIndex],
+            collection: MPL_CORE_PROGRAM_ID,
+            feeReceiverAccount: FEES_ACCOUNT,
+            clock: SYSVAR_CLOCK_PUBKEY,
+            systemProgram: SystemProgram.programId,
+            coreProgram: MPL_CORE_PROGRAM_ID,
+          },
+          signers: [owner],
+        }
+      );
+
+      const [rewardsAccount] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards", "utf8"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+
+      const strangerRewardsAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        stranger,
+        mintRewards,
+        stranger.publicKey
+      );
+
+      await assertFail(
+        program.rpc.unstakeCore(
+          {
+            accounts: {
+              staking: stakingAddress,
+              escrow: escrow,
+              rewardsAccount: rewardsAccount,
+              stakedNft: stakedNft,
+              staker: stranger.publicKey,
+              owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, stranger.publicKey),
+              asset: assets[NFTIndex],
+              collection: MPL_CORE_PROGRAM_ID,
+              rewardsMint: mintRewards,
+              stakerRewardsAccount: strangerRewardsAccount.address,
+              feeReceiverAccount: FEES_ACCOUNT,
+              rewardsTokenProgram: TOKEN_PROGRAM_ID,
+              clock: SYSVAR_CLOCK_PUBKEY,
+              systemProgram: SystemProgram.programId,
+              coreProgram: MPL_CORE_PROGRAM_ID,
+            },
+            signers: [stranger],
+          }
+        )
+      );
+    });
+
+  });
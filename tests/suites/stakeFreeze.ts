@@ -0,0 +1,380 @@
+import { expect } from "chai";
+import {
+  setProvider,
+  Provider,
+  Program,
+  workspace,
+  BN,
+  Wallet
+} from "@project-serum/anchor";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  SYSVAR_CLOCK_PUBKEY,
+} from "@solana/web3.js";
+import { Staking } from "../../target/types/staking";
+import { airdropUsers, assertFail, FEES_LAMPORTS, FEES_ACCOUNT, merkleCollectionMetaplex, findOwedRewardsAddress } from "../helpers";
+import { getAccount, createMint, getOrCreateAssociatedTokenAccount, mintTo, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { MerkleTree } from "../helpers/merkleTree";
+
+const TOKEN_METADATA_PROGRAM = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"); // Metaplex 
+
+const NFTIndex = 1;
+
+export const testStakeFreeze = (
+  state: {
+    owner: Keypair;
+    staker: Keypair;
+    stakingKey: PublicKey;
+    mintRewards: Token;
+    dailyRewards: BN;
+    start: BN;
+  },
+  provider: Provider
+) =>
+  describe("Stake frozen in the staker wallet", () => {
+    setProvider(provider);
+
+    const program = workspace.Staking as Program<Staking>;
+
+    const dailyRewards = new BN(604800000000);
+
+    const n = 10;
+    let mintRewards: Token,
+      mints: Token[],
+      holders: Keypair[],
+      ownerAccount: any;
+
+    let tree: MerkleTree;
+
+    let stakingKey: PublicKey, owner: Keypair, stranger: Keypair;
+
+    const startingAmount = new BN(10 ** 10);
+
+    beforeEach(async () => {
+      stakingKey = Keypair.generate().publicKey;
+      owner = Keypair.generate();
+      stranger = Keypair.generate();
+
+      await airdropUsers([owner, stranger], provider);
+      mintRewards = await createMint(
+        provider.connection,
+        owner,
+        owner.publicKey,
+        null,
+        9
+      );
+      // Contains one pNFT in position 0, and one NFT in position 1
+      const nfts = await merkleCollectionMetaplex(new Wallet(owner), n, provider);
+      mints = nfts.mints;
+      tree = nfts.tree;
+
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [rewards, rewardsBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+
+      const bumps = {
+        staking: stakingBump,
+        escrow: escrowBump,
+        rewards: rewardsBump,
+      };
+
+      await program.rpc.initializeStaking(
+        bumps,
+        dailyRewards,
+        state.start,
+        tree.getRootArray(),
+        { perNft: {} },
+        null,
+        {
+          accounts: {
+            stakingKey: stakingKey,
+            staking: stakingAddress,
+            escrow: escrow,
+            mint: mintRewards,
+            rewardsAccount: rewards,
+            owner: state.owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [state.owner],
+        }
+      );
+
+      // Mint reward tokens to the staking
+      await mintTo(
+          provider.connection,
+          owner,
+          mintRewards,
+          rewards,
+          owner,
+          10 ** 14
+      );
+
+    });
+
+    it("Stake and unstake a NFT token", async () => {
+
+      ownerAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          owner,
+          mints[NFTIndex],
+          owner.publicKey
+        )
+      ).address;
+
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          mints[NFTIndex].toBuffer(),
+        ],
+        program.programId
+      );
+
+      const bumps = {
+        stakedNft: stakedNftBump,
+        deposit: 0,
+      };
+
+      const [masterEddition] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from('metadata'),
+          TOKEN_METADATA_PROGRAM.toBuffer(),
+          mints[NFTIndex].toBuffer(),
+          Buffer.from('edition'),
+        ],
+        TOKEN_METADATA_PROGRAM,
+      );
+
+      await program.rpc.stakeFreeze(
+        bumps,
+        tree.getProofArray(NFTIndex),
+        new BN(NFTIndex),
+        null,
+        null,
+        {
+          accounts: {
+            staking: stakingAddress,
+            stakedNft: stakedNft,
+            staker: owner.publicKey,
+            mint: mints[NFTIndex],
+            stakerAccount: ownerAccount,
+            feeReceiverAccount: FEES_ACCOUNT,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            clock: SYSVAR_CLOCK_PUBKEY,
+            systemProgram: SystemProgram.programId,
+            masterEdition: masterEddition,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM,
+          },
+          signers: [owner],
+        }
+      );
+
+      // The NFT stays in the staker wallet, frozen
+      let stakerAccount = await getAccount(provider.connection, ownerAccount);
+      expect(stakerAccount.amount.toString()).to.equal(new BN(1).toString());
+      expect(stakerAccount.isFrozen).to.equal(true);
+      expect(stakerAccount.delegate.toString()).to.equal(stakingAddress.toString());
+
+      await new Promise(resolve => setTimeout(resolve, 1000));
+
+      // Unstake
+
+      const nftsStakedBefore = (
+        await program.account.staking.fetch(stakingAddress)
+      ).nftsStaked;
+
+      const [rewardsAccount] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards", "utf8"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+
+      const stakerRewardsAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        owner,
+        mintRewards,
+        owner.publicKey
+      );
+
+      const rewardsBefore = (await getAccount(provider.connection, rewardsAccount))
+        .amount;
+
+      await program.rpc.unstakeFreeze(
+        {
+          accounts: {
+            staking: stakingAddress,
+            escrow: escrow,
+            rewardsAccount: rewardsAccount,
+            stakedNft: stakedNft,
+            staker: owner.publicKey,
+            owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, owner.publicKey),
+            mint: mints[NFTIndex],
+            stakerAccount: ownerAccount,
+            rewardsMint: mintRewards,
+            stakerRewardsAccount: stakerRewardsAccount.address,
+            feeReceiverAccount: FEES_ACCOUNT,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            rewardsTokenProgram: TOKEN_PROGRAM_ID,
+            clock: SYSVAR_CLOCK_PUBKEY,
+            systemProgram: SystemProgram.programId,
+            masterEdition: masterEddition,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM,
+          },
+          signers: [owner],
+        }
+      );
+
+      // The rewards have been transferred to the staker
+      const stakerRewardsAfter = await getAccount(provider.connection, stakerRewardsAccount.address);
+      const rewardsAfter = (await getAccount(provider.connection, rewardsAccount))
+        .amount;
+      expect(Number(stakerRewardsAfter.amount)).to.equal(
+        Number(stakerRewardsAccount.amount) + Number(rewardsBefore) - Number(rewardsAfter)
+      );
+
+      const j = await program.account.staking.fetch(stakingAddress);
+      expect(j.nftsStaked.toString()).to.equal(
+        nftsStakedBefore.sub(new BN(1)).toString()
+      );
+
+      stakerAccount = await getAccount(provider.connection, ownerAccount);
+      expect(stakerAccount.amount.toString()).to.equal(new BN(1).toString());
+      expect(stakerAccount.isFrozen).to.equal(false);
+      expect(stakerAccount.delegate).to.equal(null);
+    });
+
+    it("Can't unstake an unowned token", async () => {
+
+      ownerAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          owner,
+          mints[NFTIndex],
+          owner.publicKey
+        )
+      ).address;
+
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          mints[NFTIndex].toBuffer(),
+        ],
+        program.programId
+      );
+
+      const [masterEddition] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from('metadata'),
+          TOKEN_METADATA_PROGRAM.toBuffer(),
+          mints[NFTIndex].toBuffer(),
+          Buffer.from('edition'),
+        ],
+        TOKEN_METADATA_PROGRAM,
+      );
+
+      await program.rpc.stakeFreeze(
+        { stakedNft: stakedNftBump, deposit: 0 },
+        tree.getProofArray(NFTIndex),
+        new BN(NFTIndex),
+        null,
+        null,
+        {
+          accounts: {
+            staking: stakingAddress,
+            stakedNft: stakedNft,
+            staker: owner.publicKey,
+            mint: mints[NFTIndex],
+            stakerAccount: ownerAccount,
+            feeReceiverAccount: FEES_ACCOUNT,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            clock: SYSVAR_CLOCK_PUBKEY,
+            systemProgram: SystemProgram.programId,
+            masterEdition: masterEddition,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM,
+          },
+          signers: [owner],
+        }
+      );
+
+      const [rewardsAccount] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards", "utf8"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+
+      const strangerRewardsAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        stranger,
+        mintRewards,
+        stranger.publicKey
+      );
+
+      await assertFail(
+        program.rpc.unstakeFreeze(
+          {
+            accounts: {
+              staking: stakingAddress,
+              escrow: escrow,
+              rewardsAccount: rewardsAccount,
+              stakedNft: stakedNft,
+              staker: stranger.publicKey,
+              owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, stranger.publicKey),
+              mint: mints[NFTIndex],
+              stakerAccount: ownerAccount,
+              rewardsMint: mintRewards,
+              stakerRewardsAccount: strangerRewardsAccount.address,
+              feeReceiverAccount: FEES_ACCOUNT,
+              tokenProgram: TOKEN_PROGRAM_ID,
+              rewardsTokenProgram: TOKEN_PROGRAM_ID,
+              clock: SYSVAR_CLOCK_PUBKEY,
+              systemProgram: SystemProgram.programId,
+              masterEdition: masterEddition,
+              tokenMetadataProgram: TOKEN_METADATA_PROGRAM,
+            },
+            signers: [stranger],
+          }
+        )
+      );
+    });
+
+  });
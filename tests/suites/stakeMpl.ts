@@ -109,6 +109,8 @@ export const testStakeMpl = (
         state.dailyRewards,
         state.start,
         tree.getRootArray(),
+        { perNft: {} },
+        null,
         {
           accounts: {
             stakingKey: stakingKey,
@@ -205,6 +207,8 @@ export const testStakeMpl = (
         bumps,
         tree.getProofArray(pNFTIndex),
         new BN(pNFTIndex),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -322,6 +326,8 @@ export const testStakeMpl = (
         bumps,
         tree.getProofArray(NFTIndex),
         new BN(NFTIndex),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -367,6 +373,226 @@ export const testStakeMpl = (
       expect(feesBalanceAfter - feesBalanceBefore).to.equal(FEES_LAMPORTS);
     });
 
+    it("Can't stake an NFT outside the verified collection", async () => {
+      const NFTIndex = 1;
+
+      ownerAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          owner,
+          mints[NFTIndex],
+          owner.publicKey
+        )
+      ).address;
+
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          mints[NFTIndex].toBuffer(),
+        ],
+        program.programId
+      );
+
+      const bumps = {
+        stakedNft: stakedNftBump,
+      };
+
+      // The test NFTs have no verified collection
+      await program.rpc.setCollection(
+        Keypair.generate().publicKey,
+        new BN(2),
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        }
+      );
+
+      const [metadataAccount] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from('metadata'),
+          TOKEN_METADATA_PROGRAM.toBuffer(),
+          mints[NFTIndex].toBuffer(),
+        ],
+        TOKEN_METADATA_PROGRAM,
+      );
+
+      const [masterEddition] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from('metadata'),
+          TOKEN_METADATA_PROGRAM.toBuffer(),
+          mints[NFTIndex].toBuffer(),
+          Buffer.from('edition'),
+        ],
+        TOKEN_METADATA_PROGRAM,
+      );
+
+      const [tokenRecordAccount] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from('metadata'),
+          TOKEN_METADATA_PROGRAM.toBuffer(),
+          mints[NFTIndex].toBuffer(),
+          Buffer.from('token_record'),
+          ownerAccount.toBuffer(),
+        ],
+        TOKEN_METADATA_PROGRAM,
+      );
+
+      // A valid allowlist proof is not enough in collection mode
+      await assertFail(
+        program.rpc.stakeMpl(
+          bumps,
+          tree.getProofArray(NFTIndex),
+          new BN(NFTIndex),
+          null,
+          null,
+          {
+            accounts: {
+              staking: stakingAddress,
+              escrow: escrow,
+              stakedNft: stakedNft,
+              staker: owner.publicKey,
+              mint: mints[NFTIndex],
+              stakerAccount: ownerAccount,
+              feeReceiverAccount: FEES_ACCOUNT,
+              clock: SYSVAR_CLOCK_PUBKEY,
+              systemProgram: SystemProgram.programId,
+              masterEdition: masterEddition,
+              metadata: metadataAccount,
+              tokenRecord: tokenRecordAccount,
+              tokenMetadataProgram: TOKEN_METADATA_PROGRAM,
+              tokenProgram: TOKEN_PROGRAM_ID,
+              authorizationRulesProgram: TOKEN_AUTH_RULES_ID,
+              authorizationRules: ruleSetPdas[NFTIndex],
+              instructions: SYSVAR_INSTRUCTIONS_PUBKEY
+            },
+            signers: [owner],
+            instructions: [ComputeBudgetProgram.setComputeUnitLimit({ units: 1400000 })]
+          }
+        )
+      );
+    });
+
+    it("Can't stake an NFT without the verified first creator", async () => {
+      const NFTIndex = 1;
+
+      ownerAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          owner,
+          mints[NFTIndex],
+          owner.publicKey
+        )
+      ).address;
+
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          mints[NFTIndex].toBuffer(),
+        ],
+        program.programId
+      );
+
+      const bumps = {
+        stakedNft: stakedNftBump,
+      };
+
+      // The first creator of the test NFTs is a random unverified key
+      await program.rpc.setCreator(
+        owner.publicKey,
+        new BN(2),
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        }
+      );
+
+      const [metadataAccount] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from('metadata'),
+          TOKEN_METADATA_PROGRAM.toBuffer(),
+          mints[NFTIndex].toBuffer(),
+        ],
+        TOKEN_METADATA_PROGRAM,
+      );
+
+      const [masterEddition] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from('metadata'),
+          TOKEN_METADATA_PROGRAM.toBuffer(),
+          mints[NFTIndex].toBuffer(),
+          Buffer.from('edition'),
+        ],
+        TOKEN_METADATA_PROGRAM,
+      );
+
+      const [tokenRecordAccount] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from('metadata'),
+          TOKEN_METADATA_PROGRAM.toBuffer(),
+          mints[NFTIndex].toBuffer(),
+          Buffer.from('token_record'),
+          ownerAccount.toBuffer(),
+        ],
+        TOKEN_METADATA_PROGRAM,
+      );
+
+      // A valid allowlist proof is not enough with a creator rule
+      await assertFail(
+        program.rpc.stakeMpl(
+          bumps,
+          tree.getProofArray(NFTIndex),
+          new BN(NFTIndex),
+          null,
+          null,
+          {
+            accounts: {
+              staking: stakingAddress,
+              escrow: escrow,
+              stakedNft: stakedNft,
+              staker: owner.publicKey,
+              mint: mints[NFTIndex],
+              stakerAccount: ownerAccount,
+              feeReceiverAccount: FEES_ACCOUNT,
+              clock: SYSVAR_CLOCK_PUBKEY,
+              systemProgram: SystemProgram.programId,
+              masterEdition: masterEddition,
+              metadata: metadataAccount,
+              tokenRecord: tokenRecordAccount,
+              tokenMetadataProgram: TOKEN_METADATA_PROGRAM,
+              tokenProgram: TOKEN_PROGRAM_ID,
+              authorizationRulesProgram: TOKEN_AUTH_RULES_ID,
+              authorizationRules: ruleSetPdas[NFTIndex],
+              instructions: SYSVAR_INSTRUCTIONS_PUBKEY
+            },
+            signers: [owner],
+            instructions: [ComputeBudgetProgram.setComputeUnitLimit({ units: 1400000 })]
+          }
+        )
+      );
+    });
+
     /*it("Fails when it's too early", async () => {
       const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
         [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
@@ -377,11 +603,12 @@ export const testStakeMpl = (
         state.dailyRewards,
         new BN(Date.now() + 1000000),
         tree.getRootArray(),
+        null,
         {
           accounts: {
             staking: stakingAddress,
             owner: state.owner.publicKey,
-            newOwner: state.owner.publicKey,
+            clock: SYSVAR_CLOCK_PUBKEY,
           },
           signers: [state.owner],
         }
@@ -407,6 +634,8 @@ export const testStakeMpl = (
         bumps,
         tree.getProofArray(indexStaked),
         new BN(indexStaked),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -434,11 +663,12 @@ export const testStakeMpl = (
         state.dailyRewards,
         state.start,
         tree.getRootArray(),
+        null,
         {
           accounts: {
             staking: stakingAddress,
             owner: state.owner.publicKey,
-            newOwner: state.owner.publicKey,
+            clock: SYSVAR_CLOCK_PUBKEY,
           },
           signers: [state.owner],
         }
@@ -478,6 +708,8 @@ export const testStakeMpl = (
         bumps,
         tree.getProofArray(indexStaked),
         new BN(indexStaked),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -546,6 +778,8 @@ export const testStakeMpl = (
             bumps,
             tree.getProofArray(indexStaked),
             new BN(indexStaked),
+            null,
+            null,
             {
               accounts: {
                 staking: stakingAddress,
@@ -596,6 +830,8 @@ export const testStakeMpl = (
             bumps,
             tree.getProofArray(indexStaked),
             new BN(indexStaked),
+            null,
+            null,
             {
               accounts: {
                 staking: stakingAddress,
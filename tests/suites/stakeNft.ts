@@ -12,11 +12,13 @@ import {
   SystemProgram,
   SYSVAR_RENT_PUBKEY,
   SYSVAR_CLOCK_PUBKEY,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  Ed25519Program,
   Transaction
 } from "@solana/web3.js";
 import { Staking } from "../../target/types/staking";
-import { airdropUsers, assertFail, merkleCollection, FEES_LAMPORTS, FEES_ACCOUNT } from "../helpers";
-import { createMint, getOrCreateAssociatedTokenAccount, mintTo, transfer, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { airdropUsers, assertFail, merkleCollection, buildLeaves, FEES_LAMPORTS, FEES_ACCOUNT, findOwedRewardsAddress, fundAddress } from "../helpers";
+import { createMint, getAccount, getOrCreateAssociatedTokenAccount, mintTo, transfer, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { MerkleTree } from "../helpers/merkleTree";
 
 export const testStakeNft = (
@@ -124,35 +126,600 @@ export const testStakeNft = (
         state.dailyRewards,
         state.start,
         tree.getRootArray(),
+        { perNft: {} },
+        null,
+        {
+          accounts: {
+            stakingKey: stakingKey,
+            staking: stakingAddress,
+            escrow: escrow,
+            mint: mintRewards,
+            rewardsAccount: rewards,
+            owner: state.owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [state.owner],
+        }
+      );
+
+      // Mint tokens to the staking
+      await mintTo(
+          provider.connection,
+          owner,
+          mintRewards,
+          rewards,
+          owner,
+          startingAmount.toNumber()
+      );
+
+    });
+
+    it("Stake a token", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          mints[indexStaked].toBuffer(),
+        ],
+        program.programId
+      );
+      const [deposit, depositBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("deposit", "utf8"),
+          mints[indexStaked].toBuffer(),
+        ],
+        program.programId
+      );
+
+      const bumps = {
+        stakedNft: stakedNftBump,
+        deposit: depositBump,
+      };
+
+      const feesBalanceBefore = await provider.connection.getBalance(FEES_ACCOUNT);
+
+      await program.rpc.stakeNft(
+        bumps,
+        tree.getProofArray(indexStaked),
+        new BN(indexStaked),
+        null,
+        null,
+        {
+          accounts: {
+            staking: stakingAddress,
+            escrow: escrow,
+            stakedNft: stakedNft,
+            staker: holders[indexStaked].publicKey,
+            mint: mints[indexStaked],
+            stakerAccount: accounts[indexStaked],
+            depositAccount: deposit,
+            feeReceiverAccount: FEES_ACCOUNT,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            clock: SYSVAR_CLOCK_PUBKEY,
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [holders[indexStaked]],
+        }
+      );
+
+      const a = await program.account.stakedNft.fetch(stakedNft);
+      const j = await program.account.staking.fetch(stakingAddress);
+
+      const timeAfter = Date.now() / 1000;
+
+      expect(j.nftsStaked.toString()).to.equal(new BN(1).toString());
+      expect(a.staker.toString()).to.equal(
+        holders[indexStaked].publicKey.toString()
+      );
+      expect(a.mint.toString()).to.equal(
+        mints[indexStaked].toString()
+      );
+      expect(a.rarityMultiplier.toString()).to.equal(new BN(indexStaked).toString());
+      expect(a.lastClaim.lte(new BN(timeAfter))).to.equal(true);
+      expect(a.lastClaim.gt(new BN(0))).to.equal(true);
+
+      const feesBalanceAfter = await provider.connection.getBalance(FEES_ACCOUNT);
+      expect(feesBalanceAfter - feesBalanceBefore).to.equal(FEES_LAMPORTS);
+    });
+
+    it("Stake a token with a lock tier", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          mints[indexStaked].toBuffer(),
+        ],
+        program.programId
+      );
+      const [deposit, depositBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("deposit", "utf8"),
+          mints[indexStaked].toBuffer(),
+        ],
+        program.programId
+      );
+
+      const bumps = {
+        stakedNft: stakedNftBump,
+        deposit: depositBump,
+      };
+
+      const day = 86400;
+      await program.rpc.setLockTiers(
+        [
+          { duration: new BN(30 * day), boost: new BN(10) },
+          { duration: new BN(90 * day), boost: new BN(50) },
+        ],
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        }
+      );
+
+      // Unknown tier
+      await assertFail(program.rpc.stakeNft(
+        bumps,
+        tree.getProofArray(indexStaked),
+        new BN(indexStaked),
+        2,
+        null,
+        {
+          accounts: {
+            staking: stakingAddress,
+            escrow: escrow,
+            stakedNft: stakedNft,
+            staker: holders[indexStaked].publicKey,
+            mint: mints[indexStaked],
+            stakerAccount: accounts[indexStaked],
+            depositAccount: deposit,
+            feeReceiverAccount: FEES_ACCOUNT,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            clock: SYSVAR_CLOCK_PUBKEY,
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [holders[indexStaked]],
+        }
+      ));
+
+      await program.rpc.stakeNft(
+        bumps,
+        tree.getProofArray(indexStaked),
+        new BN(indexStaked),
+        1,
+        null,
+        {
+          accounts: {
+            staking: stakingAddress,
+            escrow: escrow,
+            stakedNft: stakedNft,
+            staker: holders[indexStaked].publicKey,
+            mint: mints[indexStaked],
+            stakerAccount: accounts[indexStaked],
+            depositAccount: deposit,
+            feeReceiverAccount: FEES_ACCOUNT,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            clock: SYSVAR_CLOCK_PUBKEY,
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [holders[indexStaked]],
+        }
+      );
+
+      const s = await program.account.stakedNft.fetch(stakedNft);
+      expect(s.lockedUntil.toNumber()).to.equal(s.stakedAt.toNumber() + 90 * day);
+      expect(s.lockBoost.toNumber()).to.equal(50);
+      expect(s.weight.toNumber()).to.equal(Math.floor(indexStaked * 150 / 100));
+    });
+
+    it("Stake a token through a root of the registry", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [rootRegistry, rootRegistryBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("roots", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+
+      // A new drop with its own rarities, the project root is left untouched
+      const rarity = 7;
+      const dropTree = new MerkleTree(
+        buildLeaves(mints.map((mint) => ({ mint, rarityMultiplier: rarity })))
+      );
+      const name = Buffer.alloc(32);
+      name.write("drop 2");
+
+      await program.rpc.addRoot(
+        [...name],
+        dropTree.getRootArray(),
+        {
+          accounts: {
+            staking: stakingAddress,
+            rootRegistry: rootRegistry,
+            owner: state.owner.publicKey,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [state.owner],
+        }
+      );
+
+      const registry = await program.account.rootRegistry.fetch(rootRegistry);
+      expect(registry.roots.length).to.equal(1);
+      expect(registry.roots[0].active).to.equal(true);
+
+      const stake = async (index: number) => {
+        const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+          [
+            Buffer.from("staked_nft", "utf8"),
+            mints[index].toBuffer(),
+          ],
+          program.programId
+        );
+        const [deposit, depositBump] = await PublicKey.findProgramAddress(
+          [
+            Buffer.from("deposit", "utf8"),
+            mints[index].toBuffer(),
+          ],
+          program.programId
+        );
+
+        await program.rpc.stakeNft(
+          { stakedNft: stakedNftBump, deposit: depositBump },
+          dropTree.getProofArray(index),
+          new BN(rarity),
+          null,
+          0,
+          {
+            accounts: {
+              staking: stakingAddress,
+              escrow: escrow,
+              stakedNft: stakedNft,
+              staker: holders[index].publicKey,
+              mint: mints[index],
+              stakerAccount: accounts[index],
+              depositAccount: deposit,
+              feeReceiverAccount: FEES_ACCOUNT,
+              tokenProgram: TOKEN_PROGRAM_ID,
+              clock: SYSVAR_CLOCK_PUBKEY,
+              rent: SYSVAR_RENT_PUBKEY,
+              systemProgram: SystemProgram.programId,
+            },
+            remainingAccounts: [
+              { pubkey: rootRegistry, isWritable: false, isSigner: false },
+            ],
+            signers: [holders[index]],
+          }
+        );
+        return stakedNft;
+      };
+
+      const stakedNft = await stake(indexStaked);
+      const a = await program.account.stakedNft.fetch(stakedNft);
+      expect(a.rarityMultiplier.toNumber()).to.equal(rarity);
+
+      // Proofs of a retired root are rejected
+      await program.rpc.retireRoot(
+        0,
+        {
+          accounts: {
+            staking: stakingAddress,
+            rootRegistry: rootRegistry,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        }
+      );
+      await assertFail(stake(indexStaked + 1));
+    });
+
+    it("Stake tokens of a reward group up to its cap", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [rootRegistry, rootRegistryBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("roots", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+
+      // A drop earning twice the daily rewards, one NFT at a time
+      const rarity = 100;
+      const dropTree = new MerkleTree(
+        buildLeaves(mints.map((mint) => ({ mint, rarityMultiplier: rarity })))
+      );
+      const name = Buffer.alloc(32);
+      name.write("drop 2");
+
+      await program.rpc.addRoot(
+        [...name],
+        dropTree.getRootArray(),
+        {
+          accounts: {
+            staking: stakingAddress,
+            rootRegistry: rootRegistry,
+            owner: state.owner.publicKey,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [state.owner],
+        }
+      );
+      await program.rpc.setRewardGroups(
+        [
+          {
+            collection: PublicKey.default,
+            rootIndex: 0,
+            rate: new BN(200),
+            cap: new BN(1),
+            nftsStaked: new BN(0),
+          },
+        ],
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        }
+      );
+
+      const stake = async (index: number) => {
+        const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+          [
+            Buffer.from("staked_nft", "utf8"),
+            mints[index].toBuffer(),
+          ],
+          program.programId
+        );
+        const [deposit, depositBump] = await PublicKey.findProgramAddress(
+          [
+            Buffer.from("deposit", "utf8"),
+            mints[index].toBuffer(),
+          ],
+          program.programId
+        );
+
+        await program.rpc.stakeNft(
+          { stakedNft: stakedNftBump, deposit: depositBump },
+          dropTree.getProofArray(index),
+          new BN(rarity),
+          null,
+          0,
+          {
+            accounts: {
+              staking: stakingAddress,
+              escrow: escrow,
+              stakedNft: stakedNft,
+              staker: holders[index].publicKey,
+              mint: mints[index],
+              stakerAccount: accounts[index],
+              depositAccount: deposit,
+              feeReceiverAccount: FEES_ACCOUNT,
+              tokenProgram: TOKEN_PROGRAM_ID,
+              clock: SYSVAR_CLOCK_PUBKEY,
+              rent: SYSVAR_RENT_PUBKEY,
+              systemProgram: SystemProgram.programId,
+            },
+            remainingAccounts: [
+              { pubkey: rootRegistry, isWritable: false, isSigner: false },
+            ],
+            signers: [holders[index]],
+          }
+        );
+        return stakedNft;
+      };
+
+      const stakedNft = await stake(indexStaked);
+      const a = await program.account.stakedNft.fetch(stakedNft);
+      expect(a.group).to.equal(0);
+      expect(a.weight.toNumber()).to.equal(2 * rarity);
+
+      const s = await program.account.staking.fetch(stakingAddress);
+      expect(s.rewardGroups[0].nftsStaked.toNumber()).to.equal(1);
+
+      // The group is full
+      await assertFail(stake(indexStaked + 1));
+
+      // A group with staked NFTs can't be removed
+      await assertFail(
+        program.rpc.setRewardGroups([], {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        })
+      );
+
+      // Nor change its rate
+      await assertFail(
+        program.rpc.setRewardGroups(
+          [
+            {
+              collection: PublicKey.default,
+              rootIndex: 0,
+              rate: new BN(300),
+              cap: new BN(1),
+              nftsStaked: new BN(0),
+            },
+          ],
+          {
+            accounts: {
+              staking: stakingAddress,
+              owner: state.owner.publicKey,
+            },
+            signers: [state.owner],
+          }
+        )
+      );
+    });
+
+    it("Stake a token with its registered rarity and refresh it", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          mints[indexStaked].toBuffer(),
+        ],
+        program.programId
+      );
+      const [deposit, depositBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("deposit", "utf8"),
+          mints[indexStaked].toBuffer(),
+        ],
+        program.programId
+      );
+      const [rarity, rarityBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rarity", "utf8"),
+          stakingKey.toBuffer(),
+          mints[indexStaked].toBuffer(),
+        ],
+        program.programId
+      );
+      const [rewards, rewardsBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+      const [owedRewards, owedRewardsBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("owed"),
+          stakingKey.toBuffer(),
+          holders[indexStaked].publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+
+      const setRarity = (rarityMultiplier: number) =>
+        program.rpc.setRarities(
+          [{ mint: mints[indexStaked], rarityMultiplier: new BN(rarityMultiplier) }],
+          {
+            accounts: {
+              staking: stakingAddress,
+              owner: state.owner.publicKey,
+              systemProgram: SystemProgram.programId,
+            },
+            remainingAccounts: [
+              { pubkey: rarity, isWritable: true, isSigner: false },
+            ],
+            signers: [state.owner],
+          }
+        );
+
+      // Lamports sent to the rarity address beforehand don't block its creation
+      await fundAddress(provider.connection, stranger, rarity);
+      await setRarity(3);
+
+      // No proof needed, the rarity account stands for it
+      await program.rpc.stakeNft(
+        { stakedNft: stakedNftBump, deposit: depositBump },
+        [],
+        new BN(0),
+        null,
+        null,
         {
           accounts: {
-            stakingKey: stakingKey,
             staking: stakingAddress,
             escrow: escrow,
-            mint: mintRewards,
-            rewardsAccount: rewards,
-            owner: state.owner.publicKey,
+            stakedNft: stakedNft,
+            staker: holders[indexStaked].publicKey,
+            mint: mints[indexStaked],
+            stakerAccount: accounts[indexStaked],
+            depositAccount: deposit,
+            feeReceiverAccount: FEES_ACCOUNT,
             tokenProgram: TOKEN_PROGRAM_ID,
+            clock: SYSVAR_CLOCK_PUBKEY,
             rent: SYSVAR_RENT_PUBKEY,
             systemProgram: SystemProgram.programId,
           },
-          signers: [state.owner],
+          remainingAccounts: [
+            { pubkey: rarity, isWritable: false, isSigner: false },
+          ],
+          signers: [holders[indexStaked]],
         }
       );
 
-      // Mint tokens to the staking
-      await mintTo(
+      let a = await program.account.stakedNft.fetch(stakedNft);
+      expect(a.rarityMultiplier.toNumber()).to.equal(3);
+
+      // Anyone can move the staked NFT to its new rarity
+      await setRarity(5);
+      const stakerRewardsAccount = (
+        await getOrCreateAssociatedTokenAccount(
           provider.connection,
-          owner,
+          stranger,
           mintRewards,
-          rewards,
-          owner,
-          startingAmount.toNumber()
-      );
+          holders[indexStaked].publicKey
+        )
+      ).address;
 
+      await program.rpc.refreshRarity({
+        accounts: {
+          staking: stakingAddress,
+          escrow: escrow,
+          stakedNft: stakedNft,
+          rarity: rarity,
+          staker: holders[indexStaked].publicKey,
+          payer: stranger.publicKey,
+          mint: mintRewards,
+          stakerAccount: stakerRewardsAccount,
+          owedRewards: owedRewards,
+          rewardsAccount: rewards,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          clock: SYSVAR_CLOCK_PUBKEY,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+        },
+        signers: [stranger],
+      });
+
+      a = await program.account.stakedNft.fetch(stakedNft);
+      expect(a.rarityMultiplier.toNumber()).to.equal(5);
+      expect(a.weight.toNumber()).to.equal(5);
     });
 
-    it("Stake a token", async () => {
+    it("Stake a token with a rarity attestation", async () => {
       const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
         [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
         program.programId
@@ -176,54 +743,255 @@ export const testStakeNft = (
         program.programId
       );
 
-      const bumps = {
-        stakedNft: stakedNftBump,
-        deposit: depositBump,
-      };
-
-      const feesBalanceBefore = await provider.connection.getBalance(FEES_ACCOUNT);
-
-      await program.rpc.stakeNft(
-        bumps,
-        tree.getProofArray(indexStaked),
-        new BN(indexStaked),
+      const rarityAuthority = Keypair.generate();
+      await program.rpc.setRarityAuthority(
+        rarityAuthority.publicKey,
         {
           accounts: {
             staking: stakingAddress,
-            escrow: escrow,
-            stakedNft: stakedNft,
-            staker: holders[indexStaked].publicKey,
-            mint: mints[indexStaked],
-            stakerAccount: accounts[indexStaked],
-            depositAccount: deposit,
-            feeReceiverAccount: FEES_ACCOUNT,
-            tokenProgram: TOKEN_PROGRAM_ID,
-            clock: SYSVAR_CLOCK_PUBKEY,
-            rent: SYSVAR_RENT_PUBKEY,
-            systemProgram: SystemProgram.programId,
+            owner: state.owner.publicKey,
           },
-          signers: [holders[indexStaked]],
+          signers: [state.owner],
         }
       );
 
+      const attest = (rarityMultiplier: number, expiry: number) =>
+        Ed25519Program.createInstructionWithPrivateKey({
+          privateKey: rarityAuthority.secretKey,
+          message: Buffer.concat([
+            stakingKey.toBuffer(),
+            mints[indexStaked].toBuffer(),
+            new BN(rarityMultiplier).toArrayLike(Buffer, "le", 8),
+            new BN(expiry).toArrayLike(Buffer, "le", 8),
+          ]),
+        });
+
+      const stake = (attestation) =>
+        program.rpc.stakeNft(
+          { stakedNft: stakedNftBump, deposit: depositBump },
+          [],
+          new BN(0),
+          null,
+          null,
+          {
+            accounts: {
+              staking: stakingAddress,
+              escrow: escrow,
+              stakedNft: stakedNft,
+              staker: holders[indexStaked].publicKey,
+              mint: mints[indexStaked],
+              stakerAccount: accounts[indexStaked],
+              depositAccount: deposit,
+              feeReceiverAccount: FEES_ACCOUNT,
+              tokenProgram: TOKEN_PROGRAM_ID,
+              clock: SYSVAR_CLOCK_PUBKEY,
+              rent: SYSVAR_RENT_PUBKEY,
+              systemProgram: SystemProgram.programId,
+            },
+            remainingAccounts: [
+              { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isWritable: false, isSigner: false },
+            ],
+            instructions: [attestation],
+            signers: [holders[indexStaked]],
+          }
+        );
+
+      const now = Math.round(Date.now() / 1000);
+
+      // Expired attestations are refused
+      await assertFail(stake(attest(6, now - 60)));
+
+      await stake(attest(6, now + 600));
+
       const a = await program.account.stakedNft.fetch(stakedNft);
-      const j = await program.account.staking.fetch(stakingAddress);
+      expect(a.rarityMultiplier.toNumber()).to.equal(6);
+    });
 
-      const timeAfter = Date.now() / 1000;
+    it("Revalidate stakes after a root change", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [rewards, rewardsBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+      const kept = indexStaked;
+      const removed = indexStaked + 1;
 
-      expect(j.nftsStaked.toString()).to.equal(new BN(1).toString());
-      expect(a.staker.toString()).to.equal(
-        holders[indexStaked].publicKey.toString()
+      const stakedNftOf = async (index: number) =>
+        (await PublicKey.findProgramAddress(
+          [Buffer.from("staked_nft", "utf8"), mints[index].toBuffer()],
+          program.programId
+        ))[0];
+
+      for (const index of [kept, removed]) {
+        const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+          [Buffer.from("staked_nft", "utf8"), mints[index].toBuffer()],
+          program.programId
+        );
+        const [deposit, depositBump] = await PublicKey.findProgramAddress(
+          [Buffer.from("deposit", "utf8"), mints[index].toBuffer()],
+          program.programId
+        );
+        await program.rpc.stakeNft(
+          { stakedNft: stakedNftBump, deposit: depositBump },
+          tree.getProofArray(index),
+          new BN(index),
+          null,
+          null,
+          {
+            accounts: {
+              staking: stakingAddress,
+              escrow: escrow,
+              stakedNft: stakedNft,
+              staker: holders[index].publicKey,
+              mint: mints[index],
+              stakerAccount: accounts[index],
+              depositAccount: deposit,
+              feeReceiverAccount: FEES_ACCOUNT,
+              tokenProgram: TOKEN_PROGRAM_ID,
+              clock: SYSVAR_CLOCK_PUBKEY,
+              rent: SYSVAR_RENT_PUBKEY,
+              systemProgram: SystemProgram.programId,
+            },
+            signers: [holders[index]],
+          }
+        );
+      }
+
+      await program.rpc.setRevalidationGrace(
+        new BN(2),
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        }
       );
-      expect(a.mint.toString()).to.equal(
-        mints[indexStaked].toString()
+
+      // New rarities, one NFT left out of the tree
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+      const newMints = mints.filter((_, i) => i !== removed);
+      const newTree = new MerkleTree(
+        buildLeaves(newMints.map((mint) => ({ mint, rarityMultiplier: 2 * mints.indexOf(mint) })))
+      );
+      await program.rpc.setStaking(
+        state.dailyRewards,
+        state.start,
+        newTree.getRootArray(),
+        null,
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+            clock: SYSVAR_CLOCK_PUBKEY,
+          },
+          signers: [state.owner],
+        }
       );
-      expect(a.rarityMultiplier.toString()).to.equal(new BN(indexStaked).toString());
-      expect(a.lastClaim.lte(new BN(timeAfter))).to.equal(true);
-      expect(a.lastClaim.gt(new BN(0))).to.equal(true);
 
-      const feesBalanceAfter = await provider.connection.getBalance(FEES_ACCOUNT);
-      expect(feesBalanceAfter - feesBalanceBefore).to.equal(FEES_LAMPORTS);
+      const revalidate = async (index: number, proof: number[][], rarityMultiplier: number) => {
+        const [owedRewards, owedRewardsBump] = await PublicKey.findProgramAddress(
+          [
+            Buffer.from("owed"),
+            stakingKey.toBuffer(),
+            holders[index].publicKey.toBuffer(),
+          ],
+          program.programId
+        );
+        const stakerRewardsAccount = (
+          await getOrCreateAssociatedTokenAccount(
+            provider.connection,
+            stranger,
+            mintRewards,
+            holders[index].publicKey
+          )
+        ).address;
+
+        return program.rpc.revalidateStake(
+          proof,
+          null,
+          new BN(rarityMultiplier),
+          {
+            accounts: {
+              staking: stakingAddress,
+              escrow: escrow,
+              stakedNft: await stakedNftOf(index),
+              staker: holders[index].publicKey,
+              payer: stranger.publicKey,
+              mint: mintRewards,
+              stakerAccount: stakerRewardsAccount,
+              owedRewards: owedRewards,
+              rewardsAccount: rewards,
+              tokenProgram: TOKEN_PROGRAM_ID,
+              clock: SYSVAR_CLOCK_PUBKEY,
+              rent: SYSVAR_RENT_PUBKEY,
+              systemProgram: SystemProgram.programId,
+            },
+            signers: [stranger],
+          }
+        );
+      };
+
+      // Anyone can move a stake to its new rarity
+      await revalidate(kept, newTree.getProofArray(newMints.indexOf(mints[kept])), 2 * kept);
+      let a = await program.account.stakedNft.fetch(await stakedNftOf(kept));
+      expect(a.rarityMultiplier.toNumber()).to.equal(2 * kept);
+
+      // A validated stake can't be revoked
+      await assertFail(revalidate(kept, tree.getProofArray(kept), kept));
+
+      // The removed NFT keeps earning during the grace period, then it is revoked
+      await assertFail(revalidate(removed, tree.getProofArray(removed), removed));
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+
+      // Claiming without revalidating pays nothing past the grace period
+      const removedRewardsAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          stranger,
+          mintRewards,
+          holders[removed].publicKey
+        )
+      ).address;
+      const claim = async () =>
+        program.rpc.claimStaking({
+          accounts: {
+            staking: stakingAddress,
+            escrow: escrow,
+            stakedNft: await stakedNftOf(removed),
+            staker: holders[removed].publicKey,
+            owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, holders[removed].publicKey),
+            mint: mintRewards,
+            stakerAccount: removedRewardsAccount,
+            rewardsAccount: rewards,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            clock: SYSVAR_CLOCK_PUBKEY,
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [holders[removed]],
+        });
+      await claim();
+      const claimed = (await getAccount(provider.connection, removedRewardsAccount)).amount;
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+      await claim();
+      expect(Number((await getAccount(provider.connection, removedRewardsAccount)).amount)).to.equal(Number(claimed));
+
+      await revalidate(removed, tree.getProofArray(removed), removed);
+      a = await program.account.stakedNft.fetch(await stakedNftOf(removed));
+      expect(a.rarityMultiplier.toNumber()).to.equal(0);
+      expect(a.weight.toNumber()).to.equal(0);
     });
 
     it("Fails when it's too early", async () => {
@@ -236,11 +1004,12 @@ export const testStakeNft = (
         state.dailyRewards,
         new BN(Date.now() + 1000000),
         tree.getRootArray(),
+        null,
         {
           accounts: {
             staking: stakingAddress,
             owner: state.owner.publicKey,
-            newOwner: state.owner.publicKey,
+            clock: SYSVAR_CLOCK_PUBKEY,
           },
           signers: [state.owner],
         }
@@ -274,6 +1043,8 @@ export const testStakeNft = (
         bumps,
         tree.getProofArray(indexStaked),
         new BN(indexStaked),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -298,15 +1069,90 @@ export const testStakeNft = (
         state.dailyRewards,
         state.start,
         tree.getRootArray(),
+        null,
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+            clock: SYSVAR_CLOCK_PUBKEY,
+          },
+          signers: [state.owner],
+        }
+      );
+    });
+
+    it("Fails when staking has ended", async () => {
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+
+      await program.rpc.setStaking(
+        state.dailyRewards,
+        state.start,
+        tree.getRootArray(),
+        new BN(Math.round(Date.now() / 1000) + 2),
         {
           accounts: {
             staking: stakingAddress,
             owner: state.owner.publicKey,
-            newOwner: state.owner.publicKey,
+            clock: SYSVAR_CLOCK_PUBKEY,
           },
           signers: [state.owner],
         }
       );
+
+      // Wait for the staking to end
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          mints[indexStaked].toBuffer(),
+        ],
+        program.programId
+      );
+      const [deposit, depositBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("deposit", "utf8"),
+          mints[indexStaked].toBuffer(),
+        ],
+        program.programId
+      );
+
+      const bumps = {
+        stakedNft: stakedNftBump,
+        deposit: depositBump,
+      };
+
+      await assertFail(program.rpc.stakeNft(
+        bumps,
+        tree.getProofArray(indexStaked),
+        new BN(indexStaked),
+        null,
+        null,
+        {
+          accounts: {
+            staking: stakingAddress,
+            escrow: escrow,
+            stakedNft: stakedNft,
+            staker: holders[indexStaked].publicKey,
+            mint: mints[indexStaked],
+            stakerAccount: accounts[indexStaked],
+            depositAccount: deposit,
+            feeReceiverAccount: FEES_ACCOUNT,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            clock: SYSVAR_CLOCK_PUBKEY,
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [holders[indexStaked]],
+        }
+      ));
     });
 
     it("Can't stake an unowned token", async () => {
@@ -351,6 +1197,8 @@ export const testStakeNft = (
           bumps,
           tree.getProofArray(indexStaked),
           new BN(indexStaked),
+          null,
+          null,
           {
             accounts: {
               staking: stakingAddress,
@@ -426,6 +1274,8 @@ export const testStakeNft = (
         bumps,
         tree.getProofArray(indexStaked),
         new BN(indexStaked),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -481,6 +1331,8 @@ export const testStakeNft = (
         bumps,
         tree.getProofArray(indexStaked),
         new BN(indexStaked),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
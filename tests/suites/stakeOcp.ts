@@ -113,6 +113,8 @@ export const testStakeOcp = (
         state.dailyRewards,
         state.start,
         tree.getRootArray(),
+        { perNft: {} },
+        null,
         {
           accounts: {
             stakingKey: stakingKey,
@@ -168,6 +170,8 @@ export const testStakeOcp = (
         bumps,
         tree.getProofArray(indexStaked),
         new BN(indexStaked),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -220,11 +224,12 @@ export const testStakeOcp = (
         state.dailyRewards,
         new BN(Date.now() + 1000000),
         tree.getRootArray(),
+        null,
         {
           accounts: {
             staking: stakingAddress,
             owner: state.owner.publicKey,
-            newOwner: state.owner.publicKey,
+            clock: SYSVAR_CLOCK_PUBKEY,
           },
           signers: [state.owner],
         }
@@ -250,6 +255,8 @@ export const testStakeOcp = (
         bumps,
         tree.getProofArray(indexStaked),
         new BN(indexStaked),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -277,11 +284,12 @@ export const testStakeOcp = (
         state.dailyRewards,
         state.start,
         tree.getRootArray(),
+        null,
         {
           accounts: {
             staking: stakingAddress,
             owner: state.owner.publicKey,
-            newOwner: state.owner.publicKey,
+            clock: SYSVAR_CLOCK_PUBKEY,
           },
           signers: [state.owner],
         }
@@ -321,6 +329,8 @@ export const testStakeOcp = (
         bumps,
         tree.getProofArray(indexStaked),
         new BN(indexStaked),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -389,6 +399,8 @@ export const testStakeOcp = (
             bumps,
             tree.getProofArray(indexStaked),
             new BN(indexStaked),
+            null,
+            null,
             {
               accounts: {
                 staking: stakingAddress,
@@ -439,6 +451,8 @@ export const testStakeOcp = (
             bumps,
             tree.getProofArray(indexStaked),
             new BN(indexStaked),
+            null,
+            null,
             {
               accounts: {
                 staking: stakingAddress,
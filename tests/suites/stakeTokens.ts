@@ -0,0 +1,250 @@
+import { expect } from "chai";
+import {
+  setProvider,
+  Provider,
+  Program,
+  workspace,
+  BN,
+} from "@project-serum/anchor";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  SYSVAR_CLOCK_PUBKEY,
+} from "@solana/web3.js";
+import { Staking } from "../../target/types/staking";
+import { airdropUsers, assertFail, FEES_ACCOUNT, findOwedRewardsAddress, fundAddress } from "../helpers";
+import { getAccount, createMint, getOrCreateAssociatedTokenAccount, mintTo, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+
+export const testStakeTokens = (
+  state: {
+    owner: Keypair;
+    staker: Keypair;
+    stakingKey: PublicKey;
+    mintRewards: Token;
+    dailyRewards: BN;
+    start: BN;
+  },
+  provider: Provider
+) =>
+  describe("Stake fungible tokens", () => {
+    setProvider(provider);
+
+    const program = workspace.Staking as Program<Staking>;
+
+    const dailyRewards = new BN(604800000000);
+    // A whole item token earns twice the daily rewards of a base NFT
+    const itemWeight = new BN(200);
+
+    let mintRewards: PublicKey,
+      mintItem: PublicKey,
+      holder: Keypair,
+      holderItemAccount: PublicKey,
+      holderRewardsAccount: PublicKey;
+    let stakingKey: PublicKey, owner: Keypair;
+    let stakingAddress: PublicKey, escrow: PublicKey, rewards: PublicKey;
+
+    const findPosition = (mint: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("position"), stakingKey.toBuffer(), holder.publicKey.toBuffer(), mint.toBuffer()],
+        program.programId
+      )[0];
+    const findVault = (mint: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), stakingKey.toBuffer(), mint.toBuffer()],
+        program.programId
+      )[0];
+
+    const stakeAccounts = (mint: PublicKey, stakerAccount: PublicKey) => ({
+      staking: stakingAddress,
+      escrow: escrow,
+      stakedPosition: findPosition(mint),
+      staker: holder.publicKey,
+      mint: mint,
+      stakerAccount: stakerAccount,
+      vault: findVault(mint),
+      feeReceiverAccount: FEES_ACCOUNT,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      clock: SYSVAR_CLOCK_PUBKEY,
+      systemProgram: SystemProgram.programId,
+    });
+
+    beforeEach(async () => {
+      stakingKey = Keypair.generate().publicKey;
+      owner = Keypair.generate();
+      holder = Keypair.generate();
+      await airdropUsers([owner, holder], provider);
+
+      mintRewards = await createMint(provider.connection, owner, owner.publicKey, null, 9);
+      mintItem = await createMint(provider.connection, owner, owner.publicKey, null, 6);
+
+      holderItemAccount = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, holder, mintItem, holder.publicKey)
+      ).address;
+      await mintTo(provider.connection, owner, mintItem, holderItemAccount, owner, 10 * 10 ** 6);
+      holderRewardsAccount = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, holder, mintRewards, holder.publicKey)
+      ).address;
+
+      let stakingBump: number, escrowBump: number, rewardsBump: number;
+      [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking"), stakingKey.toBuffer()],
+        program.programId
+      );
+      [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow"), stakingKey.toBuffer()],
+        program.programId
+      );
+      [rewards, rewardsBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("rewards"), stakingKey.toBuffer(), mintRewards.toBuffer()],
+        program.programId
+      );
+
+      await program.rpc.initializeStaking(
+        { staking: stakingBump, escrow: escrowBump, rewards: rewardsBump },
+        dailyRewards,
+        state.start,
+        Array(32).fill(0),
+        { perNft: {} },
+        null,
+        {
+          accounts: {
+            stakingKey: stakingKey,
+            staking: stakingAddress,
+            escrow: escrow,
+            mint: mintRewards,
+            rewardsAccount: rewards,
+            owner: owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [owner],
+        }
+      );
+      await mintTo(provider.connection, owner, mintRewards, rewards, owner, 10 ** 14);
+
+      await program.rpc.setTokenWeights(
+        [{ mint: mintItem, weight: itemWeight }],
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: owner.publicKey,
+          },
+          signers: [owner],
+        }
+      );
+    });
+
+    it("Stake, claim and unstake an amount of tokens", async () => {
+      await program.rpc.stakeTokens(new BN(4 * 10 ** 6), {
+        accounts: stakeAccounts(mintItem, holderItemAccount),
+        signers: [holder],
+      });
+
+      // The tokens are in the project vault, weighted by amount
+      const vault = await getAccount(provider.connection, findVault(mintItem));
+      expect(vault.amount.toString()).to.equal((4 * 10 ** 6).toString());
+      let position = await program.account.stakedPosition.fetch(findPosition(mintItem));
+      expect(position.amount.toString()).to.equal((4 * 10 ** 6).toString());
+      expect(position.weight.toString()).to.equal(itemWeight.muln(4).toString());
+      let staking = await program.account.staking.fetch(stakingAddress);
+      expect(staking.totalWeight.toString()).to.equal(itemWeight.muln(4).toString());
+
+      await new Promise(resolve => setTimeout(resolve, 2000));
+
+      const rewardsBefore = (await getAccount(provider.connection, holderRewardsAccount)).amount;
+      await program.rpc.claimPosition({
+        accounts: {
+          staking: stakingAddress,
+          escrow: escrow,
+          stakedPosition: findPosition(mintItem),
+          staker: holder.publicKey,
+          mint: mintRewards,
+          stakerAccount: holderRewardsAccount,
+          owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, holder.publicKey),
+          rewardsAccount: rewards,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          clock: SYSVAR_CLOCK_PUBKEY,
+          systemProgram: SystemProgram.programId,
+        },
+        signers: [holder],
+      });
+      const rewardsAfter = (await getAccount(provider.connection, holderRewardsAccount)).amount;
+
+      // 4 tokens weighted 200 earn 8 times the daily rewards per second staked
+      position = await program.account.stakedPosition.fetch(findPosition(mintItem));
+      const elapsed = position.lastClaim.sub(position.stakedAt);
+      expect((rewardsAfter - rewardsBefore).toString()).to.equal(
+        dailyRewards.muln(8).mul(elapsed).divn(86400).toString()
+      );
+
+      await program.rpc.unstakeTokens(new BN(4 * 10 ** 6), {
+        accounts: {
+          ...stakeAccounts(mintItem, holderItemAccount),
+          rewardsAccount: rewards,
+          rewardsMint: mintRewards,
+          stakerRewardsAccount: holderRewardsAccount,
+          owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, holder.publicKey),
+          rewardsTokenProgram: TOKEN_PROGRAM_ID,
+        },
+        signers: [holder],
+      });
+
+      // The tokens are back and the empty position is closed
+      const holderItem = await getAccount(provider.connection, holderItemAccount);
+      expect(holderItem.amount.toString()).to.equal((10 * 10 ** 6).toString());
+      expect(await provider.connection.getAccountInfo(findPosition(mintItem))).to.equal(null);
+      staking = await program.account.staking.fetch(stakingAddress);
+      expect(staking.totalWeight.toString()).to.equal("0");
+    });
+
+    it("Stake a token whose vault address was funded beforehand", async () => {
+      await fundAddress(provider.connection, owner, findVault(mintItem));
+
+      await program.rpc.stakeTokens(new BN(10 ** 6), {
+        accounts: stakeAccounts(mintItem, holderItemAccount),
+        signers: [holder],
+      });
+
+      const vault = await getAccount(provider.connection, findVault(mintItem));
+      expect(vault.amount.toString()).to.equal((10 ** 6).toString());
+    });
+
+    it("Can't unstake more than staked", async () => {
+      await program.rpc.stakeTokens(new BN(10 ** 6), {
+        accounts: stakeAccounts(mintItem, holderItemAccount),
+        signers: [holder],
+      });
+
+      await assertFail(
+        program.rpc.unstakeTokens(new BN(2 * 10 ** 6), {
+          accounts: {
+            ...stakeAccounts(mintItem, holderItemAccount),
+            rewardsAccount: rewards,
+            rewardsMint: mintRewards,
+            stakerRewardsAccount: holderRewardsAccount,
+            owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, holder.publicKey),
+            rewardsTokenProgram: TOKEN_PROGRAM_ID,
+          },
+          signers: [holder],
+        })
+      );
+    });
+
+    it("Can't stake a token without weight", async () => {
+      const mintOther = await createMint(provider.connection, owner, owner.publicKey, null, 6);
+      const holderOtherAccount = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, holder, mintOther, holder.publicKey)
+      ).address;
+      await mintTo(provider.connection, owner, mintOther, holderOtherAccount, owner, 10 ** 6);
+
+      await assertFail(
+        program.rpc.stakeTokens(new BN(10 ** 6), {
+          accounts: stakeAccounts(mintOther, holderOtherAccount),
+          signers: [holder],
+        })
+      );
+    });
+  });
@@ -0,0 +1,297 @@
+import { expect } from "chai";
+import {
+  setProvider,
+  Provider,
+  Program,
+  workspace,
+  BN,
+} from "@project-serum/anchor";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  SYSVAR_CLOCK_PUBKEY,
+  Transaction,
+  sendAndConfirmTransaction,
+} from "@solana/web3.js";
+import { Staking } from "../../target/types/staking";
+import { airdropUsers, assertFail, merkleCollection, FEES_ACCOUNT, findOwedRewardsAddress } from "../helpers";
+import {
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  ExtensionType,
+  getAccount,
+  getMintLen,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  transfer,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import { MerkleTree } from "../helpers/merkleTree";
+
+// 1% transfer fee
+const FEE_BASIS_POINTS = 100;
+const MAX_FEE = BigInt(10 ** 18);
+
+const createTransferFeeMint = async (provider: Provider, authority: Keypair) => {
+  const mint = Keypair.generate();
+  const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+  const lamports = await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+  const tx = new Transaction().add(
+    SystemProgram.createAccount({
+      fromPubkey: authority.publicKey,
+      newAccountPubkey: mint.publicKey,
+      space: mintLen,
+      lamports,
+      programId: TOKEN_2022_PROGRAM_ID,
+    }),
+    createInitializeTransferFeeConfigInstruction(
+      mint.publicKey,
+      authority.publicKey,
+      authority.publicKey,
+      FEE_BASIS_POINTS,
+      MAX_FEE,
+      TOKEN_2022_PROGRAM_ID
+    ),
+    createInitializeMintInstruction(
+      mint.publicKey,
+      9,
+      authority.publicKey,
+      null,
+      TOKEN_2022_PROGRAM_ID
+    )
+  );
+  await sendAndConfirmTransaction(provider.connection, tx, [authority, mint]);
+  return mint.publicKey;
+};
+
+export const testToken2022Rewards = (
+  state: {
+    owner: Keypair;
+    staker: Keypair;
+    stakingKey: PublicKey;
+    mintRewards: Token;
+    dailyRewards: BN;
+    start: BN;
+  },
+  provider: Provider
+) =>
+  describe("Token-2022 rewards", () => {
+    setProvider(provider);
+
+    const program = workspace.Staking as Program<Staking>;
+
+    const dailyRewards = new BN(604800000000);
+
+    const n = 10;
+    const indexStaked = 4;
+    let mintRewards: PublicKey,
+      mints: PublicKey[],
+      holder: Keypair,
+      holderAccount: PublicKey;
+    let tree: MerkleTree;
+    let stakingKey: PublicKey, owner: Keypair;
+
+    beforeEach(async () => {
+      stakingKey = Keypair.generate().publicKey;
+      owner = Keypair.generate();
+      holder = Keypair.generate();
+      await airdropUsers([owner, holder], provider);
+
+      mintRewards = await createTransferFeeMint(provider, owner);
+
+      const nfts = await merkleCollection(owner, n, provider);
+      mints = nfts.mints;
+      tree = nfts.tree;
+      holderAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          holder,
+          mints[indexStaked],
+          holder.publicKey
+        )
+      ).address;
+      const ownerAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          owner,
+          mints[indexStaked],
+          owner.publicKey
+        )
+      ).address;
+      await transfer(provider.connection, owner, ownerAccount, holderAccount, owner, 1);
+
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [rewards, rewardsBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+
+      await program.rpc.initializeStaking(
+        { staking: stakingBump, escrow: escrowBump, rewards: rewardsBump },
+        dailyRewards,
+        state.start,
+        tree.getRootArray(),
+        { perNft: {} },
+        null,
+        {
+          accounts: {
+            stakingKey: stakingKey,
+            staking: stakingAddress,
+            escrow: escrow,
+            mint: mintRewards,
+            rewardsAccount: rewards,
+            owner: owner.publicKey,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [owner],
+        }
+      );
+
+      await mintTo(
+        provider.connection,
+        owner,
+        mintRewards,
+        rewards,
+        owner,
+        10 ** 14,
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staked_nft", "utf8"), mints[indexStaked].toBuffer()],
+        program.programId
+      );
+      const [deposit, depositBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("deposit", "utf8"), mints[indexStaked].toBuffer()],
+        program.programId
+      );
+
+      await program.rpc.stakeNft(
+        { stakedNft: stakedNftBump, deposit: depositBump },
+        tree.getProofArray(indexStaked),
+        new BN(indexStaked),
+        null,
+        null,
+        {
+          accounts: {
+            staking: stakingAddress,
+            escrow: escrow,
+            stakedNft: stakedNft,
+            staker: holder.publicKey,
+            mint: mints[indexStaked],
+            stakerAccount: holderAccount,
+            depositAccount: deposit,
+            feeReceiverAccount: FEES_ACCOUNT,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            clock: SYSVAR_CLOCK_PUBKEY,
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [holder],
+        }
+      );
+    });
+
+    const claimAccounts = async (tokenProgram: PublicKey) => {
+      const [stakingAddress] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft] = await PublicKey.findProgramAddress(
+        [Buffer.from("staked_nft", "utf8"), mints[indexStaked].toBuffer()],
+        program.programId
+      );
+      const [rewardsAccount] = await PublicKey.findProgramAddress(
+        [Buffer.from("rewards", "utf8"), stakingKey.toBuffer(), mintRewards.toBuffer()],
+        program.programId
+      );
+      const stakerAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        holder,
+        mintRewards,
+        holder.publicKey,
+        false,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+
+      return {
+        staking: stakingAddress,
+        escrow: escrow,
+        stakedNft: stakedNft,
+        staker: holder.publicKey,
+        owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, holder.publicKey),
+        mint: mintRewards,
+        stakerAccount: stakerAccount.address,
+        rewardsAccount: rewardsAccount,
+        tokenProgram: tokenProgram,
+        clock: SYSVAR_CLOCK_PUBKEY,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+      };
+    };
+
+    it("Claims rewards net of the transfer fee", async () => {
+      await new Promise(resolve => setTimeout(resolve, 2000));
+
+      const accounts = await claimAccounts(TOKEN_2022_PROGRAM_ID);
+      const rewardsBefore = (
+        await getAccount(provider.connection, accounts.rewardsAccount, undefined, TOKEN_2022_PROGRAM_ID)
+      ).amount;
+      const stakerBefore = (
+        await getAccount(provider.connection, accounts.stakerAccount, undefined, TOKEN_2022_PROGRAM_ID)
+      ).amount;
+
+      await program.rpc.claimStaking({
+        accounts,
+        signers: [holder],
+      });
+
+      const rewardsAfter = (
+        await getAccount(provider.connection, accounts.rewardsAccount, undefined, TOKEN_2022_PROGRAM_ID)
+      ).amount;
+      const stakerAfter = (
+        await getAccount(provider.connection, accounts.stakerAccount, undefined, TOKEN_2022_PROGRAM_ID)
+      ).amount;
+
+      // The mint withholds its fee, rounded up, from what the staker receives
+      const rewardsGiven = rewardsBefore - rewardsAfter;
+      const fee = (rewardsGiven * BigInt(FEE_BASIS_POINTS) + BigInt(9999)) / BigInt(10000);
+      expect(rewardsGiven > BigInt(0)).to.equal(true);
+      expect((stakerAfter - stakerBefore).toString()).to.equal((rewardsGiven - fee).toString());
+    });
+
+    it("Can't claim through another program than the token programs", async () => {
+      const accounts = await claimAccounts(SystemProgram.programId);
+
+      await assertFail(
+        program.rpc.claimStaking({
+          accounts,
+          signers: [holder],
+        })
+      );
+    });
+  });
@@ -17,7 +17,7 @@ import {
   ComputeBudgetProgram
 } from "@solana/web3.js";
 import { Staking } from "../../target/types/staking";
-import { airdropUsers, assertFail, merkleCollection, merkleCollectionOcp, FEES_LAMPORTS, FEES_ACCOUNT, merkleCollectionPNFT, merkleCollectionMetaplex } from "../helpers";
+import { airdropUsers, assertFail, merkleCollection, merkleCollectionOcp, FEES_LAMPORTS, FEES_ACCOUNT, merkleCollectionPNFT, merkleCollectionMetaplex, findOwedRewardsAddress } from "../helpers";
 import { getAccount, createMint, getOrCreateAssociatedTokenAccount, mintTo, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { MerkleTree } from "../helpers/merkleTree";
 import {
@@ -111,6 +111,8 @@ export const testUnstakeMpl = (
         dailyRewards,
         state.start,
         tree.getRootArray(),
+        { perNft: {} },
+        null,
         {
           accounts: {
             stakingKey: stakingKey,
@@ -204,6 +206,8 @@ export const testUnstakeMpl = (
         bumps,
         tree.getProofArray(pNFTIndex),
         new BN(pNFTIndex),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -269,6 +273,7 @@ export const testUnstakeMpl = (
             rewardsAccount: rewardsAccount,
             stakedNft: stakedNft,
             staker: owner.publicKey,
+            owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, owner.publicKey),
             mint: mints[pNFTIndex],
             stakerAccount: ownerAccount,
             rewardsMint: mintRewards,
@@ -280,6 +285,7 @@ export const testUnstakeMpl = (
             tokenRecord: tokenRecordAccount,
             tokenMetadataProgram: TOKEN_METADATA_PROGRAM,
             tokenProgram: TOKEN_PROGRAM_ID,
+            rewardsTokenProgram: TOKEN_PROGRAM_ID,
             authorizationRulesProgram: TOKEN_AUTH_RULES_ID,
             authorizationRules: ruleSetPdas[pNFTIndex],
             clock: SYSVAR_CLOCK_PUBKEY,
@@ -397,6 +403,8 @@ export const testUnstakeMpl = (
         bumps,
         tree.getProofArray(NFTIndex),
         new BN(NFTIndex),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -466,6 +474,7 @@ export const testUnstakeMpl = (
             rewardsAccount: rewardsAccount,
             stakedNft: stakedNft,
             staker: owner.publicKey,
+            owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, owner.publicKey),
             mint: mints[NFTIndex],
             stakerAccount: ownerAccount,
             rewardsMint: mintRewards,
@@ -477,6 +486,7 @@ export const testUnstakeMpl = (
             tokenRecord: tokenRecordAccount,
             tokenMetadataProgram: TOKEN_METADATA_PROGRAM,
             tokenProgram: TOKEN_PROGRAM_ID,
+            rewardsTokenProgram: TOKEN_PROGRAM_ID,
             authorizationRulesProgram: TOKEN_AUTH_RULES_ID,
             authorizationRules: ruleSetPdas[NFTIndex],
             clock: SYSVAR_CLOCK_PUBKEY,
@@ -520,6 +530,162 @@ export const testUnstakeMpl = (
       expect(ownerAccount.amount.toString()).to.equal(new BN(1).toString());
     });
 
+    it("Can't unstake a locked NFT token", async () => {
+
+      ownerAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          owner,
+          mints[NFTIndex],
+          owner.publicKey
+        )
+      ).address;
+
+      const [stakingAddress, stakingBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("staking", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [escrow, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow", "utf8"), stakingKey.toBuffer()],
+        program.programId
+      );
+      const [stakedNft, stakedNftBump] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("staked_nft", "utf8"),
+          mints[NFTIndex].toBuffer(),
+        ],
+        program.programId
+      );
+
+      const bumps = {
+        stakedNft: stakedNftBump,
+      };      
+
+      const [metadataAccount] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from('metadata'),
+          TOKEN_METADATA_PROGRAM.toBuffer(),
+          mints[NFTIndex].toBuffer(),
+        ],
+        TOKEN_METADATA_PROGRAM,
+      );
+
+      const [masterEddition] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from('metadata'),
+          TOKEN_METADATA_PROGRAM.toBuffer(),
+          mints[NFTIndex].toBuffer(),
+          Buffer.from('edition'),
+        ],
+        TOKEN_METADATA_PROGRAM,
+      );
+
+      const [tokenRecordAccount] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from('metadata'),
+          TOKEN_METADATA_PROGRAM.toBuffer(),
+          mints[NFTIndex].toBuffer(),
+          Buffer.from('token_record'),
+          ownerAccount.toBuffer(),
+        ],
+        TOKEN_METADATA_PROGRAM,
+      );
+
+      await program.rpc.setLockTiers(
+        [{ duration: new BN(30 * 86400), boost: new BN(10) }],
+        {
+          accounts: {
+            staking: stakingAddress,
+            owner: state.owner.publicKey,
+          },
+          signers: [state.owner],
+        }
+      );
+
+      await program.rpc.stakeMpl(
+        bumps,
+        tree.getProofArray(NFTIndex),
+        new BN(NFTIndex),
+        0,
+        null,
+        {
+          accounts: {
+            staking: stakingAddress,
+            escrow: escrow,
+            stakedNft: stakedNft,
+            staker: owner.publicKey,
+            mint: mints[NFTIndex],
+            stakerAccount: ownerAccount,
+            feeReceiverAccount: FEES_ACCOUNT,
+            clock: SYSVAR_CLOCK_PUBKEY,
+            systemProgram: SystemProgram.programId,
+            masterEdition: masterEddition,
+            metadata: metadataAccount,
+            tokenRecord: tokenRecordAccount,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            authorizationRulesProgram: TOKEN_AUTH_RULES_ID,
+            authorizationRules: ruleSetPdas[NFTIndex],
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY
+          },
+          signers: [owner],
+          instructions: [ComputeBudgetProgram.setComputeUnitLimit({ units: 1400000 })]
+        }
+      );
+
+      const stakingBefore = await program.account.staking.fetch(stakingAddress);
+
+      const [rewardsAccount] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards", "utf8"),
+          stakingBefore.key.toBuffer(),
+          stakingBefore.mint.toBuffer(),
+        ],
+        program.programId
+      );
+
+      const stakerRewardsAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        owner,
+        mintRewards,
+        owner.publicKey
+      );
+
+      await assertFail(program.rpc.unstakeMpl(
+        {
+          accounts: {
+            staking: stakingAddress,
+            escrow: escrow,
+            rewardsAccount: rewardsAccount,
+            stakedNft: stakedNft,
+            staker: owner.publicKey,
+            owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, owner.publicKey),
+            mint: mints[NFTIndex],
+            stakerAccount: ownerAccount,
+            rewardsMint: mintRewards,
+            stakerRewardsAccount: stakerRewardsAccount.address,
+            feeReceiverAccount: FEES_ACCOUNT,
+            systemProgram: SystemProgram.programId,
+            masterEdition: masterEddition,
+            metadata: metadataAccount,
+            tokenRecord: tokenRecordAccount,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            rewardsTokenProgram: TOKEN_PROGRAM_ID,
+            authorizationRulesProgram: TOKEN_AUTH_RULES_ID,
+            authorizationRules: ruleSetPdas[NFTIndex],
+            clock: SYSVAR_CLOCK_PUBKEY,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY
+          },
+          signers: [owner],
+          instructions: [ComputeBudgetProgram.setComputeUnitLimit({ units: 1400000 })]
+        }
+      ));
+
+      const s = await program.account.stakedNft.fetch(stakedNft);
+      expect(s.lockedUntil.toNumber()).to.equal(s.stakedAt.toNumber() + 30 * 86400);
+    });
+
     it("Stake-Unstake-Restake a pNFT token", async () => {
 
       ownerAccount = (
@@ -585,6 +751,8 @@ export const testUnstakeMpl = (
         bumps,
         tree.getProofArray(pNFTIndex),
         new BN(pNFTIndex),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -640,6 +808,7 @@ export const testUnstakeMpl = (
             rewardsAccount: rewardsAccount,
             stakedNft: stakedNft,
             staker: owner.publicKey,
+            owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, owner.publicKey),
             mint: mints[pNFTIndex],
             stakerAccount: ownerAccount,
             rewardsMint: mintRewards,
@@ -651,6 +820,7 @@ export const testUnstakeMpl = (
             tokenRecord: tokenRecordAccount,
             tokenMetadataProgram: TOKEN_METADATA_PROGRAM,
             tokenProgram: TOKEN_PROGRAM_ID,
+            rewardsTokenProgram: TOKEN_PROGRAM_ID,
             authorizationRulesProgram: TOKEN_AUTH_RULES_ID,
             authorizationRules: ruleSetPdas[pNFTIndex],
             clock: SYSVAR_CLOCK_PUBKEY,
@@ -665,6 +835,8 @@ export const testUnstakeMpl = (
         bumps,
         tree.getProofArray(pNFTIndex),
         new BN(pNFTIndex),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -756,6 +928,8 @@ export const testUnstakeMpl = (
         bumps,
         tree.getProofArray(NFTIndex),
         new BN(NFTIndex),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -811,6 +985,7 @@ export const testUnstakeMpl = (
             rewardsAccount: rewardsAccount,
             stakedNft: stakedNft,
             staker: owner.publicKey,
+            owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, owner.publicKey),
             mint: mints[NFTIndex],
             stakerAccount: ownerAccount,
             rewardsMint: mintRewards,
@@ -822,6 +997,7 @@ export const testUnstakeMpl = (
             tokenRecord: tokenRecordAccount,
             tokenMetadataProgram: TOKEN_METADATA_PROGRAM,
             tokenProgram: TOKEN_PROGRAM_ID,
+            rewardsTokenProgram: TOKEN_PROGRAM_ID,
             authorizationRulesProgram: TOKEN_AUTH_RULES_ID,
             authorizationRules: ruleSetPdas[NFTIndex],
             clock: SYSVAR_CLOCK_PUBKEY,
@@ -836,6 +1012,8 @@ export const testUnstakeMpl = (
         bumps,
         tree.getProofArray(NFTIndex),
         new BN(NFTIndex),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -18,7 +18,7 @@ import {
   ComputeBudgetProgram
 } from "@solana/web3.js";
 import { Staking } from "../../target/types/staking";
-import { airdropUsers, FEES_LAMPORTS, FEES_ACCOUNT, merkleCollectionMetaplex } from "../helpers";
+import { airdropUsers, FEES_LAMPORTS, FEES_ACCOUNT, merkleCollectionMetaplex, findOwedRewardsAddress } from "../helpers";
 import { getAccount, createMint, getOrCreateAssociatedTokenAccount, mintTo, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { MerkleTree } from "../helpers/merkleTree";
 import {
@@ -108,6 +108,8 @@ export const testUnstakeMplCustodial = (
         state.dailyRewards,
         state.start,
         tree.getRootArray(),
+        { perNft: {} },
+        null,
         {
           accounts: {
             stakingKey: stakingKey,
@@ -222,6 +224,8 @@ export const testUnstakeMplCustodial = (
         bumps,
         tree.getProofArray(NFTIndex),
         new BN(NFTIndex),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -276,6 +280,7 @@ export const testUnstakeMplCustodial = (
             rewardsAccount: rewardsAccount,
             stakedNft: stakedNft,
             staker: owner.publicKey,
+            owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, owner.publicKey),
             mint: mints[NFTIndex],
             rewardsMint: mintRewards,
             stakerRewardsAccount: stakerRewardsAccount.address,
@@ -289,6 +294,7 @@ export const testUnstakeMplCustodial = (
             ownerTokenRecord: ownerTokenRecordAccount,
             tokenMetadataProgram: TOKEN_METADATA_PROGRAM,
             tokenProgram: TOKEN_PROGRAM_ID,
+            rewardsTokenProgram: TOKEN_PROGRAM_ID,
             authorizationRulesProgram: TOKEN_AUTH_RULES_ID,
             authorizationRules: ruleSetPdas[NFTIndex],
             ataProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
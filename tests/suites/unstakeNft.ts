@@ -14,7 +14,7 @@ import {
   SYSVAR_CLOCK_PUBKEY,
 } from "@solana/web3.js";
 import { Staking } from "../../target/types/staking";
-import { airdropUsers, assertFail, merkleCollection, FEES_LAMPORTS, FEES_ACCOUNT } from "../helpers";
+import { airdropUsers, assertFail, merkleCollection, FEES_LAMPORTS, FEES_ACCOUNT, findOwedRewardsAddress } from "../helpers";
 import { createMint, getOrCreateAssociatedTokenAccount, mintTo, transfer, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { MerkleTree } from "../helpers/merkleTree";
 
@@ -120,6 +120,8 @@ export const testUnstakeNft = (
         state.dailyRewards,
         state.start,
         tree.getRootArray(),
+        { perNft: {} },
+        null,
         {
           accounts: {
             stakingKey: stakingKey,
@@ -160,6 +162,8 @@ export const testUnstakeNft = (
         bumpsStakedNft,
         tree.getProofArray(indexStaked),
         new BN(indexStaked),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -218,18 +222,39 @@ export const testUnstakeNft = (
 
       const feesBalanceBefore = await provider.connection.getBalance(FEES_ACCOUNT);
 
+      const [rewardsAccount] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards", "utf8"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+      const stakerRewardsAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        holders[indexStaked],
+        mintRewards,
+        holders[indexStaked].publicKey
+      );
+
       await program.rpc.unstakeNft({
         accounts: {
           staking: stakingAddress,
           escrow: escrow,
+          rewardsAccount: rewardsAccount,
           stakedNft: stakedNft,
           staker: holders[indexStaked].publicKey,
+          owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, holders[indexStaked].publicKey),
           mint: mints[indexStaked],
           stakerAccount: stakerAccount.address,
+          rewardsMint: mintRewards,
+          stakerRewardsAccount: stakerRewardsAccount.address,
           depositAccount: deposit,
           feeReceiverAccount: FEES_ACCOUNT,
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
+          rewardsTokenProgram: TOKEN_PROGRAM_ID,
+          clock: SYSVAR_CLOCK_PUBKEY,
         },
         signers: [holders[indexStaked]],
       });
@@ -291,19 +316,40 @@ export const testUnstakeNft = (
           stranger.publicKey
         );
 
+      const [rewardsAccount] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("rewards", "utf8"),
+          stakingKey.toBuffer(),
+          mintRewards.toBuffer(),
+        ],
+        program.programId
+      );
+      const stakerRewardsAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        stranger,
+        mintRewards,
+        stranger.publicKey
+      );
+
       await assertFail(
         program.rpc.unstakeNft({
           accounts: {
             staking: stakingAddress,
             escrow: escrow,
+            rewardsAccount: rewardsAccount,
             stakedNft: stakedNft,
             staker: stranger.publicKey,
+            owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, stranger.publicKey),
             mint: mints[indexStaked],
             stakerAccount: stakerAccount.address,
+            rewardsMint: mintRewards,
+            stakerRewardsAccount: stakerRewardsAccount.address,
             depositAccount: deposit,
             feeReceiverAccount: FEES_ACCOUNT,
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
+            rewardsTokenProgram: TOKEN_PROGRAM_ID,
+            clock: SYSVAR_CLOCK_PUBKEY,
           },
           signers: [stranger],
         })
@@ -16,7 +16,7 @@ import {
   SYSVAR_INSTRUCTIONS_PUBKEY,
 } from "@solana/web3.js";
 import { Staking } from "../../target/types/staking";
-import { airdropUsers, assertFail, merkleCollectionOcp, FEES_LAMPORTS, FEES_ACCOUNT } from "../helpers";
+import { airdropUsers, assertFail, merkleCollectionOcp, FEES_LAMPORTS, FEES_ACCOUNT, findOwedRewardsAddress } from "../helpers";
 import { getAccount, createMint, getOrCreateAssociatedTokenAccount, mintTo, transfer, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { MerkleTree } from "../helpers/merkleTree";
 import {
@@ -112,6 +112,8 @@ export const testUnstakeOcp = (
         state.dailyRewards,
         state.start,
         tree.getRootArray(),
+        { perNft: {} },
+        null,
         {
           accounts: {
             stakingKey: stakingKey,
@@ -157,6 +159,8 @@ export const testUnstakeOcp = (
         bumpsStakedNft,
         tree.getProofArray(indexStaked),
         new BN(indexStaked),
+        null,
+        null,
         {
           accounts: {
             staking: stakingAddress,
@@ -243,6 +247,7 @@ export const testUnstakeOcp = (
             rewardsAccount: rewardsAccount,
             stakedNft: stakedNft,
             staker: owner.publicKey,
+            owedRewards: await findOwedRewardsAddress(program.programId, stakingKey, owner.publicKey),
             mint: mints[indexStaked],
             rewardsMint: mintRewards,
             stakerRewardsAccount: stakerRewardsAccount.address,
@@ -254,7 +259,7 @@ export const testUnstakeOcp = (
             ocpProgram: OCP_PROGRAM,
             cmtProgram: CMT_PROGRAM,
             clock: SYSVAR_CLOCK_PUBKEY,
-            tokenProgram: TOKEN_PROGRAM_ID,
+            rewardsTokenProgram: TOKEN_PROGRAM_ID,
             instructions: SYSVAR_INSTRUCTIONS_PUBKEY
           },
           signers: [owner],
@@ -11,6 +11,7 @@ import {
   Keypair,
   SystemProgram,
   SYSVAR_RENT_PUBKEY,
+  SYSVAR_CLOCK_PUBKEY,
 } from "@solana/web3.js";
 import { Staking } from "../../target/types/staking";
 import { airdropUsers, assertFail, merkleCollection } from "../helpers";
@@ -119,6 +120,8 @@ export const testWithdrawRewards = (
         state.dailyRewards,
         state.start,
         tree.getRootArray(),
+        { perNft: {} },
+        null,
         {
           accounts: {
             stakingKey: stakingKey,
@@ -173,7 +176,7 @@ export const testWithdrawRewards = (
 
       const withdraw = new BN(10 ** 9);
 
-      await program.rpc.withdrawRewards(withdraw, {
+      await program.rpc.withdrawRewards(withdraw, false, {
         accounts: {
           staking: stakingAddress,
           escrow: escrow,
@@ -182,6 +185,7 @@ export const testWithdrawRewards = (
           owner: owner.publicKey,
           ownerAccount: ownerAccount.address,
           tokenProgram: TOKEN_PROGRAM_ID,
+          clock: SYSVAR_CLOCK_PUBKEY,
         },
         signers: [owner],
       });